@@ -1,23 +1,38 @@
 //app_state is the struct resposible for storing variables that control the app
 //and handles keypress events that change those variables
 
-use crate::colors::load_theme_from_file;
+use crate::cache;
 use crate::colors::{self, Theme};
-
+use crate::config::Config;
+use crate::errors::{self, ApiError};
+use crate::history::{self, HistoryEntry, PlaySource};
+use crate::playlist_form::{FormStep, PendingAdd, PlaylistAction, PlaylistForm, PRIVACY_OPTIONS};
+use crate::search_filters::{SearchFilters, FILTER_FIELDS};
+use crate::stats::{StatsGrouping, StatsRange};
 use crate::utilities;
 use crate::utilities::play_playlist;
-use anyhow::Result;
+use crate::seen;
+use crate::comments::CommentsView;
+use crate::description;
+use crate::playlist_files::{self, FileEntry, PlaylistFile};
+use crate::local_playlists::{self, LocalPlaylist, LocalVideo};
+use crate::youtube::{Access, Channel, CommentOrder, FeedVideo, Playlist, PlaylistEntry, Rating, SearchResult, Subscription, TokenSource, Video, VideoDetails, YouTubeClient};
+use anyhow::{bail, Result};
+use futures::future::BoxFuture;
 use crossterm::event::KeyEvent;
 use crossterm::{event::KeyCode, terminal::disable_raw_mode};
 use oauth2::TokenResponse;
 use std::fs;
-use std::io;
-use std::os::linux::raw::stat;
 use std::path::Path;
-use crate::authenticate::{load_token, OAuthToken};
-use once_cell::sync::OnceCell;
+use crate::authenticate::{self, load_token, OAuthToken, SavedToken};
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
-static OAUTH_TOKEN: OnceCell<Mutex<Option<OAuthToken>>> = OnceCell::new();
+static OAUTH_TOKEN: OnceCell<Mutex<Option<SavedToken>>> = OnceCell::new();
+//only one refresh runs at a time, everyone else waits for its result
+static REFRESH_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+//tokens are refreshed this long before they would expire
+pub const TOKEN_REFRESH_MARGIN_MINUTES: i64 = 5;
 
 
 
@@ -26,38 +41,176 @@ pub enum Event<I> {
     Input(I),
     Tick,
     Message(String),
+    //replaces the text of the status bar
+    Status(String),
+    //an api call is waiting to be retried, None once it succeeded or gave up
+    RetryStatus(Option<String>),
+    //a page of playlists fetched in the background: (generation, playlists, total number of playlists)
+    PlaylistsPage(u64, Vec<Playlist>, u32),
+    //the background playlist fetch with this generation is finished: (generation, whether all of the user's playlists were listed)
+    PlaylistsDone(u64, bool),
+    //the subscriptions of the user: (generation, subscriptions)
+    Subscriptions(u64, Vec<Subscription>),
+    //the latest uploads of the subscriptions, the background fetch is finished after this
+    Feed(u64, Vec<FeedVideo>),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum MenuItem {
     Account,
     Commands,
+    History,
     Home,
     Playlists,
     Search,
+    Stats,
+    Subscriptions,
+}
+
+//what picking a search result or history item by number does
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelectionAction {
+    Play,
+    AddToPlaylist,
+    Rate(Rating),
+    //shows the channel that uploaded the picked result
+    OpenChannel,
+    //subscribes to the channel behind the picked result, or unsubscribes if already subscribed
+    ToggleSubscription,
+    //adds the picked video to the save for later playlist from the config
+    SaveForLater,
+    //opens the details pane of the picked video
+    ShowDetails,
+}
+
+impl SelectionAction {
+    //the prompt shown while the number is typed
+    pub fn prompt(self) -> &'static str {
+        match self {
+            SelectionAction::Play => "Play number: ",
+            SelectionAction::AddToPlaylist => "Add to a playlist, number: ",
+            SelectionAction::Rate(Rating::Like) => "Like number: ",
+            SelectionAction::Rate(Rating::Dislike) => "Dislike number: ",
+            SelectionAction::Rate(Rating::Unrated) => "Remove the rating of number: ",
+            SelectionAction::OpenChannel => "Open the channel of number: ",
+            SelectionAction::ToggleSubscription => "Subscribe or unsubscribe, number: ",
+            SelectionAction::SaveForLater => "Save for later, number: ",
+            SelectionAction::ShowDetails => "Show the details of number: ",
+        }
+    }
 }
 
 pub struct AppState {
     pub messages: Vec<String>,
+    //last error or notice, shown in the status bar under every tab
+    pub status: String,
+    //shown instead of status while an api call is being retried
+    pub retry_status: Option<String>,
     pub authenticated: bool,
     pub active_menu_item: MenuItem,
 
-    pub playlists: Vec<(String, String)>,
+    pub playlists: Vec<Playlist>,
+    pub playlists_total: u32,
+    //set by the event handler, the tui loop picks it up and starts fetching in the background
+    pub playlists_requested: bool,
+    //the next fetch ignores cached responses
+    pub playlists_force_refresh: bool,
+    pub playlists_loading: bool,
+    //whether the user's youtube playlists were all listed by the last fetch, not just the local ones
+    pub playlists_loaded: bool,
+    //bumped on every request so pages from an older fetch can be ignored
+    pub playlists_generation: u64,
     pub playlist_number_input: String,
     pub playlist_selection_mode: bool,
+    //the highlighted playlist, the list scrolls to keep it on screen, typing a number moves it there
+    pub playlist_cursor: usize,
+    //the dialog for creating, renaming, changing privacy or deleting a playlist, if one is open
+    pub playlist_form: Option<PlaylistForm>,
+    //the playlist whose videos are shown instead of the list of playlists
+    pub playlist_open: Option<Playlist>,
+    pub playlist_items: Vec<PlaylistEntry>,
+    //the highlighted video of the open playlist
+    pub playlist_item_cursor: usize,
+    //the video id or link being typed to add to the open local playlist
+    pub local_video_input: Option<String>,
+    //what the search or history selection does with the picked video
+    pub selection_action: SelectionAction,
+    //the user's ratings of the videos on screen, by video id
+    pub ratings: HashMap<String, Rating>,
+    //the subscription to each channel on screen by channel id, None if the user isn't subscribed
+    pub subscribed: HashMap<String, Option<String>>,
+    //the picked video, waiting for the number of the playlist
+    pub add_to_playlist: Option<PendingAdd>,
 
     pub search_input: String,
     pub search_attempted: bool,
     pub search_typing: bool,
-    pub search_result: Vec<(String, String, String, String)>,
+    pub search_result: Vec<SearchResult>,
+    //the query and filters of the last search, loading more pages has to repeat them exactly
+    pub search_query: String,
+    pub search_query_filters: SearchFilters,
+    //None once the last page of results is loaded
+    pub search_next_page: Option<String>,
     pub search_selection_mode: bool,
     pub search_number_input: String,
+    //filters stay set for every search until the app is closed
+    pub search_filters: SearchFilters,
+    pub search_filters_open: bool,
+    //the row of the filter panel that is highlighted
+    pub search_filter_cursor: usize,
+    pub search_filter_editing: bool,
+    pub search_filter_input: String,
+
+    pub history: Vec<HistoryEntry>,
+    pub history_filter_input: String,
+    pub history_typing: bool,
+    pub history_selection_mode: bool,
+    pub history_number_input: String,
+
+    pub subscriptions: Vec<Subscription>,
+    //set by the event handler, the tui loop fetches the subscriptions and their uploads in the background
+    pub subscriptions_requested: bool,
+    pub subscriptions_force_refresh: bool,
+    pub subscriptions_loading: bool,
+    pub subscriptions_generation: u64,
+    pub feed: Vec<FeedVideo>,
+    pub feed_cursor: usize,
+    //video ids of the feed that were watched or marked as seen, kept in seen.json
+    pub seen: HashSet<String>,
+    //picking a subscription by number to open its channel
+    pub subscription_selection_mode: bool,
+    pub subscription_number_input: String,
+
+    //the channel page, drawn over the active tab until Esc closes it
+    pub channel_open: Option<Channel>,
+    pub channel_uploads: Vec<PlaylistEntry>,
+    pub channel_playlists: Vec<Playlist>,
+    pub channel_upload_cursor: usize,
+    pub channel_playlist_cursor: usize,
+    //Tab moves the highlight between the uploads and the playlists
+    pub channel_playlists_focused: bool,
+
+    //the details pane of a video, drawn over everything else until Esc closes it
+    pub details_open: Option<VideoDetails>,
+    pub details_scroll: u16,
+    //the number of the timestamp to play from
+    pub details_input: String,
+    //the comments of the video in the details pane, drawn over it until Esc closes them
+    pub comments: Option<CommentsView>,
+    //keeps playing related tracks after a single video, starts out as set in the config
+    pub autoplay: bool,
+
+    pub stats_range: StatsRange,
+    pub stats_grouping: StatsGrouping,
 
     pub selected_theme: Theme,
     pub themes: Vec<String>,
     pub theme_selection_mode: bool,
     pub theme_number_input: String,
     pub theme_selected_path: String,
+
+    pub config: Config,
+    pub youtube: YouTubeClient,
 }
 
 //main keypress event handler for the tui
@@ -70,306 +223,1624 @@ pub async fn event_handler(
 
     //handling token errors 
     {
-    if let Err(e) = load_and_set_token() {
-        eprintln!("Failed to load token: {}", e);
+    //without token.json nobody signed in yet, public reads can still use the api key
+    if Path::new("token.json").exists() {
+        if let Err(e) = load_and_set_token() {
+            eprintln!("Failed to load token: {}", e);
+        }
     }
 
     //hanling keypress events
-    match event {
-        Event::Input(key_event) => match key_event.code {
-            //saving search input into app state if search  is active and the user is typing
-            KeyCode::Char(c)
-                if state.active_menu_item == MenuItem::Search && state.search_typing =>
-            {
-                state.search_input.push(c);
+    let key_event = match event {
+        Event::Input(key_event) => key_event,
+        //do nothing on any other events
+        _ => return Ok(false),
+    };
+    match key_event.code {
+        //typing the value of a text filter in the search filter panel
+        KeyCode::Char(c) if state.search_filter_editing => {
+            state.search_filter_input.push(c);
+        }
+        KeyCode::Backspace if state.search_filter_editing => {
+            state.search_filter_input.pop();
+        }
+        //the typed value is checked before it is saved, an empty value clears the filter
+        KeyCode::Enter if state.search_filter_editing => {
+            let field = FILTER_FIELDS[state.search_filter_cursor];
+            match state.search_filters.set_text(field, &state.search_filter_input) {
+                Ok(()) => state.status.clear(),
+                Err(e) => state.status = e,
+            }
+            state.search_filter_editing = false;
+            state.search_filter_input.clear();
+        }
+        KeyCode::Esc if state.search_filter_editing => {
+            state.search_filter_editing = false;
+            state.search_filter_input.clear();
+        }
+        //picking the playlist a video is added to, it takes every key while it is open
+        KeyCode::Esc if state.add_to_playlist.is_some() => {
+            state.add_to_playlist = None;
+            state.status = "Cancelled.".to_string();
+        }
+        KeyCode::Char(digit) if state.add_to_playlist.is_some() && digit.is_ascii_digit() => {
+            if let Some(pending) = state.add_to_playlist.as_mut() {
+                pending.input.push(digit);
+            }
+        }
+        KeyCode::Backspace if state.add_to_playlist.is_some() => {
+            if let Some(pending) = state.add_to_playlist.as_mut() {
+                pending.input.pop();
+            }
+        }
+        KeyCode::Enter if state.add_to_playlist.is_some() => {
+            if let Some(pending) = state.add_to_playlist.take() {
+                match pending.target(&state.playlists).cloned() {
+                    Ok(playlist) => match add_to(state, &playlist, pending.video.clone()).await {
+                        Ok(()) => {
+                            state.status = format!("Added {} to {}.", pending.video.title, playlist.title);
+                            //the open playlist has to show the new video too
+                            if state.playlist_open.as_ref().is_some_and(|open| open.id == playlist.id) {
+                                load_playlist_items(state, playlist, true).await;
+                            }
+                        }
+                        Err(e) => {
+                            state.status = format!("Failed to add {} to {}: {}", pending.video.title, playlist.title, errors::describe(&e));
+                        }
+                    },
+                    Err(message) => {
+                        state.status = message;
+                        state.add_to_playlist = Some(PendingAdd { input: String::new(), ..pending });
+                    }
+                }
+            }
+        }
+        _ if state.add_to_playlist.is_some() => {}
+        //the playlist dialog takes every key while it is open
+        KeyCode::Esc if state.playlist_form.is_some() => {
+            state.playlist_form = None;
+            state.status = "Cancelled.".to_string();
+        }
+        KeyCode::Char(c) if state.playlist_form.as_ref().is_some_and(|form| form.is_typing()) => {
+            if let Some(form) = state.playlist_form.as_mut() {
+                if !form.is_numeric() || c.is_ascii_digit() {
+                    form.input.push(c);
+                }
+                if form.is_numeric() {
+                    if let Some(idx) = list_index(&form.input, state.playlists.len()) {
+                        state.playlist_cursor = idx;
+                    }
+                }
+            }
+        }
+        KeyCode::Backspace if state.playlist_form.is_some() => {
+            if let Some(form) = state.playlist_form.as_mut() {
+                form.input.pop();
+            }
+        }
+        KeyCode::Up | KeyCode::Down
+            if state.playlist_form.as_ref().is_some_and(|form| form.step == FormStep::Privacy) =>
+        {
+            if let Some(form) = state.playlist_form.as_mut() {
+                form.cycle_privacy(key_event.code == KeyCode::Down);
+            }
+        }
+        //deleting only happens after an explicit y
+        KeyCode::Char('y') if state.playlist_form.as_ref().is_some_and(|form| form.step == FormStep::Confirm) => {
+            if let Some(form) = state.playlist_form.take() {
+                apply_playlist_form(state, form).await;
+            }
+        }
+        KeyCode::Char('n') if state.playlist_form.as_ref().is_some_and(|form| form.step == FormStep::Confirm) => {
+            state.playlist_form = None;
+            state.status = "Cancelled.".to_string();
+        }
+        KeyCode::Enter if state.playlist_form.as_ref().is_some_and(|form| form.step != FormStep::Confirm) => {
+            if let Some(mut form) = state.playlist_form.take() {
+                match form.submit(&state.playlists) {
+                    Ok(true) => apply_playlist_form(state, form).await,
+                    Ok(false) => state.playlist_form = Some(form),
+                    Err(e) => {
+                        state.status = e;
+                        state.playlist_form = Some(form);
+                    }
+                }
+            }
+        }
+        //any other key is ignored, so typing q in the dialog doesn't quit
+        _ if state.playlist_form.is_some() => {}
+        //typing the id or link of a video for the open local playlist
+        KeyCode::Esc if state.local_video_input.is_some() => {
+            state.local_video_input = None;
+        }
+        KeyCode::Char(c) if state.local_video_input.is_some() => {
+            if let Some(input) = state.local_video_input.as_mut() {
+                input.push(c);
+            }
+        }
+        KeyCode::Backspace if state.local_video_input.is_some() => {
+            if let Some(input) = state.local_video_input.as_mut() {
+                input.pop();
+            }
+        }
+        KeyCode::Enter if state.local_video_input.is_some() => {
+            let input = state.local_video_input.take().unwrap_or_default();
+            match (playlist_files::video_id_from(&input), state.playlist_open.clone()) {
+                (Some(video_id), Some(playlist)) => add_video_by_id(state, playlist, video_id).await,
+                (None, _) => {
+                    state.status = format!("{} isn't a YouTube video id or link.", input.trim());
+                    state.local_video_input = Some(input);
+                }
+                (_, None) => {}
+            }
+        }
+        _ if state.local_video_input.is_some() => {}
+        //the comments view takes every key while it is open
+        KeyCode::Esc if state.comments.is_some() => {
+            state.comments = None;
+        }
+        KeyCode::Up | KeyCode::Down if state.comments.is_some() => {
+            if let Some(comments) = state.comments.as_mut() {
+                comments.move_cursor(key_event.code == KeyCode::Down);
+            }
+        }
+        //shows or hides the replies of the highlighted comment, the ones youtube didn't send with it are fetched first
+        KeyCode::Enter if state.comments.is_some() => {
+            let missing = state
+                .comments
+                .as_ref()
+                .filter(|comments| comments.needs_replies())
+                .and_then(|comments| comments.highlighted())
+                .map(|thread| thread.id.clone());
+            if let Some(id) = missing {
+                match state.youtube.comment_replies(&id, state.config.max_pages).await {
+                    Ok(replies) => {
+                        if let Some(thread) = state.comments.as_mut().and_then(|comments| comments.threads.iter_mut().find(|thread| thread.id == id)) {
+                            thread.replies = replies;
+                        }
+                    }
+                    Err(e) => state.status = format!("Failed to load the replies: {}", errors::describe(&e)),
+                }
+            }
+            if let Some(comments) = state.comments.as_mut() {
+                comments.toggle_replies();
+            }
+        }
+        //switching between top comments and newest first starts over from the first page
+        KeyCode::Char('o') if state.comments.is_some() => {
+            if let Some(comments) = state.comments.clone() {
+                open_comments(state, comments.video_id, comments.video_title, comments.order.toggle()).await;
+            }
+        }
+        KeyCode::Char('m') if state.comments.is_some() => {
+            let next = state.comments.as_ref().and_then(|comments| {
+                comments.next_page.clone().map(|token| (comments.video_id.clone(), comments.order, token))
+            });
+            if let Some((video_id, order, token)) = next {
+                match state.youtube.comment_threads(&video_id, order, Some(&token)).await {
+                    Ok(page) => {
+                        if let Some(comments) = state.comments.as_mut() {
+                            comments.append(page);
+                        }
+                    }
+                    Err(e) => state.status = format!("Failed to load more comments: {}", errors::describe(&e)),
+                }
+            }
+        }
+        _ if state.comments.is_some() => {}
+        //the details pane takes every key while it is open, it can be opened from the channel page too
+        KeyCode::Esc if state.details_open.is_some() => {
+            state.details_open = None;
+            state.details_input.clear();
+        }
+        KeyCode::Up if state.details_open.is_some() => {
+            state.details_scroll = state.details_scroll.saturating_sub(1);
+        }
+        KeyCode::Down if state.details_open.is_some() => {
+            state.details_scroll = state.details_scroll.saturating_add(1);
+        }
+        KeyCode::Char(digit) if state.details_open.is_some() && digit.is_ascii_digit() && state.details_input.len() < 3 => {
+            state.details_input.push(digit);
+        }
+        KeyCode::Backspace if state.details_open.is_some() => {
+            state.details_input.pop();
+        }
+        //Enter plays the video, or plays it from the timestamp whose number was typed
+        KeyCode::Enter if state.details_open.is_some() => {
+            if let Some(details) = state.details_open.clone() {
+                let timestamps = description::extract_timestamps(&details.description);
+                let start = if state.details_input.is_empty() {
+                    Some(0)
+                } else {
+                    state
+                        .details_input
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|idx| *idx > 0)
+                        .and_then(|idx| timestamps.get(idx - 1))
+                        .map(|timestamp| timestamp.secs)
+                };
+                match start {
+                    Some(start) => {
+                        play_video(state, &details.id, &details.title, &details.uploader, PlaySource::Details, start);
+                    }
+                    None => state.status = "Timestamp number out of range.".to_string(),
+                }
+            }
+            state.details_input.clear();
+        }
+        KeyCode::Char('c') if state.details_open.is_some() => {
+            if let Some(details) = state.details_open.clone() {
+                open_comments(state, details.id, details.title, CommentOrder::Relevance).await;
             }
-            
-            //going into search mode if s is pressed, clearing previous search data too
-            KeyCode::Char('s') => {
-                state.active_menu_item = MenuItem::Search;
-                state.search_input.clear(); // reset previous input
-                state.search_result.clear(); // clear old results
-                state.search_attempted = false;
-                state.search_typing = true;
+        }
+        _ if state.details_open.is_some() => {}
+        //the channel page takes every key while it is open
+        KeyCode::Esc if state.channel_open.is_some() => {
+            state.channel_open = None;
+            state.channel_uploads.clear();
+            state.channel_playlists.clear();
+        }
+        KeyCode::Tab if state.channel_open.is_some() => {
+            state.channel_playlists_focused = !state.channel_playlists_focused;
+        }
+        KeyCode::Up if state.channel_open.is_some() => {
+            if state.channel_playlists_focused {
+                state.channel_playlist_cursor = state.channel_playlist_cursor.saturating_sub(1);
+            } else {
+                state.channel_upload_cursor = state.channel_upload_cursor.saturating_sub(1);
             }
+        }
+        KeyCode::Down if state.channel_open.is_some() => {
+            if state.channel_playlists_focused {
+                state.channel_playlist_cursor = (state.channel_playlist_cursor + 1).min(state.channel_playlists.len().saturating_sub(1));
+            } else {
+                state.channel_upload_cursor = (state.channel_upload_cursor + 1).min(state.channel_uploads.len().saturating_sub(1));
+            }
+        }
+        //plays the highlighted upload or playlist
+        KeyCode::Enter if state.channel_open.is_some() => {
+            if state.channel_playlists_focused {
+                if let Some(playlist) = state.channel_playlists.get(state.channel_playlist_cursor).cloned() {
+                    state.messages.push(format!("Playing playlist: {}", playlist.title));
+                    if let Err(e) = play_playlist(&state.youtube, &playlist.id, state.config.max_pages).await {
+                        state.status = format!("Failed to play playlist: {}", errors::describe(&e));
+                    }
+                }
+            } else if let Some(entry) = state.channel_uploads.get(state.channel_upload_cursor).cloned() {
+                play_video(state, &entry.video_id, &entry.title, &entry.uploader, PlaySource::Channel, 0);
+            }
+        }
+        KeyCode::Char('d') if state.channel_open.is_some() && !state.channel_playlists_focused => {
+            if let Some(entry) = state.channel_uploads.get(state.channel_upload_cursor).cloned() {
+                open_details(state, &entry.video_id).await;
+            }
+        }
+        KeyCode::Char('u') if state.channel_open.is_some() => {
+            if let Some(channel) = state.channel_open.clone() {
+                toggle_subscription(state, channel.id, channel.title).await;
+            }
+        }
+        //plays the uploads one after the other, starting at the highlighted one
+        KeyCode::Char('P') if state.channel_open.is_some() => {
+            let uploads = state.channel_uploads.get(state.channel_upload_cursor..).unwrap_or_default();
+            if let Err(e) = utilities::play_entries(uploads, PlaySource::Channel) {
+                state.status = format!("Failed to play the uploads: {}", errors::describe(&e));
+            }
+        }
+        _ if state.channel_open.is_some() => {}
+        //saving search input into app state if search  is active and the user is typing
+        KeyCode::Char(c)
+            if state.active_menu_item == MenuItem::Search && state.search_typing =>
+        {
+            state.search_input.push(c);
+        }
+        //saving the history filter while the user is typing it
+        KeyCode::Char(c)
+            if state.active_menu_item == MenuItem::History && state.history_typing =>
+        {
+            state.history_filter_input.push(c);
+        }
+        
+        //going into search mode if s is pressed, clearing previous search data too
+        KeyCode::Char('s') => {
+            state.active_menu_item = MenuItem::Search;
+            state.search_input.clear(); // reset previous input
+            state.search_result.clear(); // clear old results
+            state.search_next_page = None;
+            state.search_attempted = false;
+            state.search_typing = true;
+            state.search_filters_open = false;
+        }
 
-            //starts the search itself if search mode is active and the user is typing
+        //starts the search itself if search mode is active and the user is typing
 
-            KeyCode::Enter if state.active_menu_item == MenuItem::Search && state.search_typing => 
+        KeyCode::Enter if state.active_menu_item == MenuItem::Search && state.search_typing => 
+        {
+        
+            state.search_attempted = true;
+            state.search_query = state.search_input.clone();
+            state.search_query_filters = state.search_filters.clone();
+            //the client takes care of the oauth token for the search
+            let results = state.youtube.search(&state.search_query, &state.search_query_filters, None).await;
+            match results 
+                {
+                //if the search_videos method returns something, it sets search_result to the results
+                Ok(page) => 
+                    {
+                    state.search_result = page.results;
+                    load_ratings(state).await;
+                    load_subscription_states(state).await;
+                    state.search_next_page = page.next_page_token;
+                    state.status.clear();
+                    }
+                //tells the user what went wrong and what to do about it
+                Err(e) => 
+                    {
+                    state.status = format!("Failed to search videos: {}", errors::describe(&e));
+                    }
+                }
+        }
+        //loading the next page of results and adding it to the end of the list
+        KeyCode::Char('m') if state.active_menu_item == MenuItem::Search && !state.search_filters_open => 
+        {
+            match state.search_next_page.clone() 
             {
-            
-                state.search_attempted = true;
-                //retriving oauth token for the search
-                if let Some(token)= get_token()                
+                Some(token) => 
+                {
+                    let results = state.youtube.search(&state.search_query, &state.search_query_filters, Some(&token)).await;
+                    match results 
                     {
-                    let maybe_results = utilities::search_videos(token.access_token().secret(),
-                        &state.search_input).await.ok();
-                        //if the search_videos method returns something, it sets search_result to the results
-                    if let Some(new) = maybe_results 
+                        Ok(page) => 
                         {
-                        state.search_result = new;
+                            state.search_result.extend(page.results);
+                            load_ratings(state).await;
+                            load_subscription_states(state).await;
+                            state.search_next_page = page.next_page_token;
+                            state.status.clear();
                         }
-                    //sends error message if the search fails    
-                    else 
+                        Err(e) => 
                         {
-                        state.messages.push("Failed to search videos".to_string());
+                            state.status = format!("Failed to load more results: {}", errors::describe(&e));
                         }
                     }
-
-                else 
-                    {
-                    state.messages.push("No token available".to_string());
-                    }
+                }
+                None if state.search_attempted => state.status = "There are no more results for this search.".to_string(),
+                None => {}
             }
-            //selection for search items
-            KeyCode::Char('b') if state.active_menu_item == MenuItem::Search => 
-            {
-                state.search_selection_mode = true;
-                state.search_number_input.clear();
+        }
+        //opening or closing the search filter panel, not while typing the search itself
+        KeyCode::Char('f') if state.active_menu_item == MenuItem::Search => {
+            state.search_filters_open = !state.search_filters_open;
+            state.search_selection_mode = false;
+        }
+        //moving between the filters
+        KeyCode::Up if state.active_menu_item == MenuItem::Search && state.search_filters_open => {
+            state.search_filter_cursor = state.search_filter_cursor.saturating_sub(1);
+        }
+        KeyCode::Down if state.active_menu_item == MenuItem::Search && state.search_filters_open => {
+            state.search_filter_cursor = (state.search_filter_cursor + 1).min(FILTER_FIELDS.len() - 1);
+        }
+        //choice filters move on to their next value, text filters start being edited
+        KeyCode::Enter if state.active_menu_item == MenuItem::Search && state.search_filters_open => {
+            let field = FILTER_FIELDS[state.search_filter_cursor];
+            if field.is_text() {
+                state.search_filter_editing = true;
+                state.search_filter_input = state.search_filters.value(field);
+            } else {
+                state.search_filters.cycle(field);
             }
-            //saving the number of search results for playback
-            KeyCode::Char(digit) if state.search_selection_mode && digit.is_ascii_digit() => 
+        }
+        //resetting every filter to its default
+        KeyCode::Char('x') if state.active_menu_item == MenuItem::Search && state.search_filters_open => {
+            state.search_filters = SearchFilters::default();
+        }
+        KeyCode::Esc if state.active_menu_item == MenuItem::Search && state.search_filters_open => {
+            state.search_filters_open = false;
+        }
+        //selection for search items, b plays the picked one, l adds it to a playlist
+        //+ likes it, - dislikes it, = removes the rating, v opens the channel behind it and u (un)subscribes to that channel
+        //w saves it for later and d shows its details
+        KeyCode::Char(c @ 'b') | KeyCode::Char(c @ 'l') | KeyCode::Char(c @ '+') | KeyCode::Char(c @ '-') | KeyCode::Char(c @ '=') | KeyCode::Char(c @ 'v') | KeyCode::Char(c @ 'u') | KeyCode::Char(c @ 'w') | KeyCode::Char(c @ 'd')
+            if state.active_menu_item == MenuItem::Search => 
+        {
+            state.search_filters_open = false;
+            state.selection_action = selection_action_for_key(c);
+            state.search_selection_mode = true;
+            state.search_number_input.clear();
+        }
+        //saving the number of search results for playback
+        KeyCode::Char(digit)
+            if state.search_selection_mode
+                && digit.is_ascii_digit()
+                && state.search_number_input.len() < 3 =>
+        {
+            state.search_number_input.push(digit);
+        }
+        
+        //the selected search result is added to a playlist or rated instead of being played
+        KeyCode::Enter if state.search_selection_mode && state.selection_action != SelectionAction::Play => 
+        {
+            let result = state
+                .search_number_input
+                .parse::<usize>()
+                .ok()
+                .filter(|idx| *idx > 0)
+                .and_then(|idx| state.search_result.get(idx - 1))
+                .cloned();
+            match (result, state.selection_action) 
             {
-                if state.search_number_input.len() < 2 
-                {
-                    state.search_number_input.push(digit);
+                (Some(result), SelectionAction::OpenChannel) => open_channel(state, result.channel().0).await,
+                (Some(result), SelectionAction::ToggleSubscription) => {
+                    let (channel_id, title) = result.channel();
+                    toggle_subscription(state, channel_id.to_string(), title.to_string()).await
                 }
+                (Some(SearchResult::Video(video)), SelectionAction::SaveForLater) => save_for_later(state, local_video(&video)).await,
+                (Some(SearchResult::Video(video)), SelectionAction::ShowDetails) => open_details(state, &video.id).await,
+                (Some(SearchResult::Video(video)), SelectionAction::Rate(rating)) => rate_video(state, video.id, video.title, rating).await,
+                (Some(SearchResult::Video(video)), _) => start_add_to_playlist(state, local_video(&video)),
+                (Some(_), _) => state.status = "Only videos can be added to a playlist, saved for later, rated or shown in detail.".to_string(),
+                (None, _) => state.messages.push("Search result number out of range.".to_string()),
             }
-            
-            //starting playback after selecting a search result by number
-            KeyCode::Enter if state.search_selection_mode => 
+            state.search_selection_mode = false;
+            state.selection_action = SelectionAction::Play;
+            state.search_number_input.clear();
+        }
+        //starting playback after selecting a search result by number
+        KeyCode::Enter if state.search_selection_mode => 
+        {
+            if let Ok(idx) = state.search_number_input.parse::<usize>() 
             {
-                if let Ok(idx) = state.search_number_input.parse::<usize>() 
+                //only attempting playback if the index makes sense
+                if idx > 0 && idx <= state.search_result.len() 
                 {
-                    //only attempting playback if the index makes sense
-                    if idx > 0 && idx <= state.search_result.len() 
-                    {
-                            //getting video and starting playback
-                            if let Some((_title, _duration, _uploader, video_id)) =
-                                state.search_result.get(idx - 1)
+                        //each kind of result has its own action
+                        match state.search_result.get(idx - 1).cloned()
+                        {
+                            //videos are played right away
+                            Some(SearchResult::Video(video)) => 
+                            {
+                                play_video(state, &video.id, &video.title, &video.uploader, PlaySource::Search, 0);
+                            }
+                            //playlists are played the same way as the ones in the Playlists tab
+                            Some(SearchResult::Playlist { id, title, .. }) => 
                             {
-                                utilities::play_song_by_id(video_id);
-                                state.messages.push(format!("Playing video {}", video_id));
-                            } 
+                                state.messages.push(format!("Playing playlist: {}", title));
+                                if let Err(e) = play_playlist(&state.youtube, &id, state.config.max_pages).await 
+                                {
+                                    state.status = format!("Failed to play playlist: {}", errors::describe(&e));
+                                }
+                            }
+                            //channels open their channel page
+                            Some(SearchResult::Channel { id, .. }) => open_channel(state, &id).await,
                             //error handling
-                            else 
+                            None => 
                             {
-                                state.messages.push("Video not found.".to_string());
+                                state.messages.push("Search result not found.".to_string());
                             }
-                    
-                    }
-                    //error handling here too 
-                    else {
-                        state.messages.push("Video number out of range.".to_string());
-                    }
-                } 
-                else 
-                {
-                    state.messages.push("Invalid number input.".to_string());
+                        }
+                
                 }
-                //resetting selection variables
-                state.search_selection_mode = false;
-                state.search_number_input.clear();
+                //error handling here too 
+                else {
+                    state.messages.push("Search result number out of range.".to_string());
+                }
+            } 
+            else 
+            {
+                state.messages.push("Invalid number input.".to_string());
             }
+            //resetting selection variables
+            state.search_selection_mode = false;
+            state.search_number_input.clear();
+        }
 
-            //cancellation of selection by pressing Esc
-            KeyCode::Esc if state.search_selection_mode => 
-            {
-                state.search_selection_mode = false;
-                state.search_number_input.clear();
-                state.messages.push("Search selection cancelled.".to_string());
+        //cancellation of selection by pressing Esc
+        KeyCode::Esc if state.search_selection_mode => 
+        {
+            state.search_selection_mode = false;
+            state.selection_action = SelectionAction::Play;
+            state.search_number_input.clear();
+            state.messages.push("Search selection cancelled.".to_string());
+        }
+        //cancellation of searching
+        KeyCode::Esc if state.active_menu_item == MenuItem::Search && state.search_typing => 
+        {
+            state.search_typing = false;
+            state.search_input.clear();
+        }
+        //allows use of backspace while searching
+        KeyCode::Backspace if state.active_menu_item == MenuItem::Search && state.search_typing =>
+        {
+            state.search_input.pop();
+        }
+        //changing into history mode and reading the local history file
+        KeyCode::Char('i') => {
+            state.active_menu_item = MenuItem::History;
+            state.history_filter_input.clear();
+            state.history_typing = false;
+            state.history_selection_mode = false;
+            state.history_number_input.clear();
+            match history::load_history() {
+                Ok(entries) => state.history = entries,
+                Err(e) => state.messages.push(format!("Failed to load history: {}", e)),
             }
-            //cancellation of searching
-            KeyCode::Esc if state.active_menu_item == MenuItem::Search && state.search_typing => 
-            {
-                state.search_typing = false;
-                state.search_input.clear();
+        }
+        //starts filtering the history by title or uploader
+        KeyCode::Char('f') if state.active_menu_item == MenuItem::History => {
+            state.history_typing = true;
+            state.history_selection_mode = false;
+            state.history_filter_input.clear();
+        }
+        //the filter is applied while typing, enter just stops editing it
+        KeyCode::Enter if state.active_menu_item == MenuItem::History && state.history_typing => {
+            state.history_typing = false;
+        }
+        //cancelling the filter
+        KeyCode::Esc if state.active_menu_item == MenuItem::History && state.history_typing => {
+            state.history_typing = false;
+            state.history_filter_input.clear();
+        }
+        KeyCode::Backspace if state.active_menu_item == MenuItem::History && state.history_typing => {
+            state.history_filter_input.pop();
+        }
+        //selection for history items, with the same keys as in search
+        //number 1 is the track played last, so it can be added to a playlist or rated right after listening
+        KeyCode::Char(c @ 'b') | KeyCode::Char(c @ 'l') | KeyCode::Char(c @ '+') | KeyCode::Char(c @ '-') | KeyCode::Char(c @ '=') | KeyCode::Char(c @ 'd')
+            if state.active_menu_item == MenuItem::History =>
+        {
+            state.history_selection_mode = true;
+            state.selection_action = selection_action_for_key(c);
+            state.history_number_input.clear();
+        }
+        KeyCode::Char(digit)
+            if state.history_selection_mode
+                && digit.is_ascii_digit()
+                && state.history_number_input.len() < digits(state.history.len()) =>
+        {
+            state.history_number_input.push(digit);
+        }
+        //replaying a history item, the numbers refer to the filtered list that is on screen
+        KeyCode::Enter if state.history_selection_mode => {
+            let filtered = history::filter_history(&state.history, &state.history_filter_input);
+            let picked = match state.history_number_input.parse::<usize>() {
+                Ok(idx) if idx > 0 && idx <= filtered.len() => Some(filtered[idx - 1].clone()),
+                Ok(_) => {
+                    state.messages.push("History number out of range.".to_string());
+                    None
+                }
+                Err(_) => {
+                    state.messages.push("Invalid number input.".to_string());
+                    None
+                }
+            };
+            match (picked, state.selection_action) {
+                (Some(entry), SelectionAction::Rate(rating)) => rate_video(state, entry.video_id, entry.title, rating).await,
+                (Some(entry), SelectionAction::AddToPlaylist) => start_add_to_playlist(
+                    state,
+                    LocalVideo { video_id: entry.video_id, title: entry.title, uploader: entry.uploader, channel_id: String::new() },
+                ),
+                (Some(entry), SelectionAction::ShowDetails) => open_details(state, &entry.video_id).await,
+                (Some(entry), SelectionAction::Play) => {
+                    play_video(state, &entry.video_id, &entry.title, &entry.uploader, PlaySource::History, 0);
+                    if let Ok(entries) = history::load_history() {
+                        state.history = entries;
+                    }
+                }
+                //history doesn't keep the channel id, so v and u aren't offered here, w is only for search results
+                (Some(_), SelectionAction::OpenChannel)
+                | (Some(_), SelectionAction::ToggleSubscription)
+                | (Some(_), SelectionAction::SaveForLater) => {}
+                (None, _) => {}
             }
-            //allows use of backspace while searching
-            KeyCode::Backspace if state.active_menu_item == MenuItem::Search && state.search_typing =>
-            {
-                state.search_input.pop();
+            state.history_selection_mode = false;
+            state.selection_action = SelectionAction::Play;
+            state.history_number_input.clear();
+        }
+        KeyCode::Esc if state.history_selection_mode => {
+            state.history_selection_mode = false;
+            state.selection_action = SelectionAction::Play;
+            state.history_number_input.clear();
+            state.messages.push("History selection cancelled.".to_string());
+        }
+        //changing into the subscriptions tab, they are only fetched the first time
+        KeyCode::Char('o') => {
+            state.active_menu_item = MenuItem::Subscriptions;
+            if state.subscriptions.is_empty() && !state.subscriptions_loading {
+                state.subscriptions_generation += 1;
+                state.subscriptions_requested = true;
             }
-            //quitting
-            KeyCode::Char('q') => 
-            {
-                disable_raw_mode()?;
-                terminal.show_cursor()?;
-                return Ok(true);
+        }
+        //fetching the subscriptions and the feed again, skipping the response cache
+        KeyCode::Char('r') if state.active_menu_item == MenuItem::Subscriptions => {
+            state.subscriptions_generation += 1;
+            state.subscriptions_requested = true;
+            state.subscriptions_force_refresh = true;
+        }
+        KeyCode::Up if state.active_menu_item == MenuItem::Subscriptions => {
+            state.feed_cursor = state.feed_cursor.saturating_sub(1);
+        }
+        KeyCode::Down if state.active_menu_item == MenuItem::Subscriptions => {
+            state.feed_cursor = (state.feed_cursor + 1).min(state.feed.len().saturating_sub(1));
+        }
+        //v and the number of a subscription opens its channel page
+        KeyCode::Char('v') if state.active_menu_item == MenuItem::Subscriptions => {
+            state.subscription_selection_mode = true;
+            state.subscription_number_input.clear();
+        }
+        KeyCode::Char(digit)
+            if state.subscription_selection_mode
+                && digit.is_ascii_digit()
+                && state.subscription_number_input.len() < 4 =>
+        {
+            state.subscription_number_input.push(digit);
+        }
+        KeyCode::Backspace if state.subscription_selection_mode => {
+            state.subscription_number_input.pop();
+        }
+        KeyCode::Enter if state.subscription_selection_mode => {
+            let picked = state
+                .subscription_number_input
+                .parse::<usize>()
+                .ok()
+                .filter(|idx| *idx > 0)
+                .and_then(|idx| state.subscriptions.get(idx - 1))
+                .map(|subscription| subscription.channel_id.clone());
+            state.subscription_selection_mode = false;
+            state.subscription_number_input.clear();
+            match picked {
+                Some(channel_id) => open_channel(state, &channel_id).await,
+                None => state.messages.push("Subscription number out of range.".to_string()),
+            }
+        }
+        KeyCode::Esc if state.subscription_selection_mode => {
+            state.subscription_selection_mode = false;
+            state.subscription_number_input.clear();
+        }
+        //playing the highlighted upload, which also marks it as seen
+        KeyCode::Enter if state.active_menu_item == MenuItem::Subscriptions => {
+            if let Some(video) = state.feed.get(state.feed_cursor).cloned() {
+                play_video(state, &video.video_id, &video.title, &video.channel_title, PlaySource::Feed, 0);
+                state.seen.insert(video.video_id);
+                save_seen(state);
             }
-            //changing into accounts mode
-            KeyCode::Char('a') => {
-                state.messages.clear();
-                state.authenticated = false;
-                state.active_menu_item = MenuItem::Account;
+        }
+        KeyCode::Char('d') if state.active_menu_item == MenuItem::Subscriptions => {
+            if let Some(video) = state.feed.get(state.feed_cursor).cloned() {
+                open_details(state, &video.video_id).await;
             }
+        }
+        //w marks the highlighted upload as seen or unseen, W marks all of them as seen
+        KeyCode::Char('w') if state.active_menu_item == MenuItem::Subscriptions => {
+            if let Some(video) = state.feed.get(state.feed_cursor) {
+                if !state.seen.remove(&video.video_id) {
+                    state.seen.insert(video.video_id.clone());
+                }
+                save_seen(state);
+            }
+        }
+        KeyCode::Char('W') if state.active_menu_item == MenuItem::Subscriptions => {
+            state.seen.extend(state.feed.iter().map(|video| video.video_id.clone()));
+            save_seen(state);
+        }
+        //turning autoplay radio on or off for the rest of the session
+        KeyCode::Char('R') => {
+            state.autoplay = !state.autoplay;
+            state.status = if state.autoplay {
+                "Autoplay radio is on, related tracks keep playing after a single video.".to_string()
+            } else {
+                "Autoplay radio is off.".to_string()
+            };
+        }
+        //changing into stats mode, the stats are computed from the local history
+        KeyCode::Char('t') => {
+            state.active_menu_item = MenuItem::Stats;
+            match history::load_history() {
+                Ok(entries) => state.history = entries,
+                Err(e) => state.messages.push(format!("Failed to load history: {}", e)),
+            }
+        }
+        //cycling the date range of the stats
+        KeyCode::Char('r') if state.active_menu_item == MenuItem::Stats => {
+            state.stats_range = state.stats_range.next();
+        }
+        //switching the bar chart between days and weeks
+        KeyCode::Char('g') if state.active_menu_item == MenuItem::Stats => {
+            state.stats_grouping = state.stats_grouping.toggle();
+        }
+        //quitting
+        KeyCode::Char('q') => 
+        {
+            disable_raw_mode()?;
+            terminal.show_cursor()?;
+            return Ok(true);
+        }
+        //changing into accounts mode
+        KeyCode::Char('a') => {
+            state.messages.clear();
+            state.authenticated = false;
+            state.active_menu_item = MenuItem::Account;
+        }
 
-            //changing into commands mode
-            KeyCode::Char('c') => state.active_menu_item = MenuItem::Commands,
+        //changing into commands mode
+        KeyCode::Char('c') => state.active_menu_item = MenuItem::Commands,
 
-            //changing into home mode
-            KeyCode::Char('h') => 
-            {
-                state.active_menu_item = MenuItem::Home;
-                let maybe_themes = utilities::get_theme_files();
-                state.themes = maybe_themes?;
-            }
+        //changing into home mode
+        KeyCode::Char('h') => 
+        {
+            state.active_menu_item = MenuItem::Home;
+            let maybe_themes = utilities::get_theme_files();
+            state.themes = maybe_themes?;
+        }
 
-            //changing to playlist mode and fetching users playlists
-            KeyCode::Char('p') => {
-                state.active_menu_item = MenuItem::Playlists;
-                //attempts getting tokens and fetching playlists
-                let maybe_playlists = if let Some(token) = get_token() 
-                {
-                    utilities::list_playlists(token.access_token().secret()).await.ok()
-                } 
-                else
-                {
-                    let _ = std::fs::write("tui_debug.log", "No token available\n");
-                    None
+        //changing to playlist mode and asking the tui loop to fetch the users playlists
+        //the pages are streamed into state.playlists as they arrive
+        KeyCode::Char('p') => {
+            state.active_menu_item = MenuItem::Playlists;
+            state.playlist_open = None;
+            state.playlist_cursor = 0;
+            state.playlists.clear();
+            state.playlists_total = 0;
+            state.playlists_generation += 1;
+            state.playlists_requested = true;
+        }
+        //fetching the playlists again, skipping the response cache
+        KeyCode::Char('r') if state.active_menu_item == MenuItem::Playlists => {
+            state.playlists.clear();
+            state.playlists_total = 0;
+            state.playlists_generation += 1;
+            state.playlists_requested = true;
+            state.playlists_force_refresh = true;
+        }
+        //moving through the list of playlists
+        KeyCode::Up if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_cursor = state.playlist_cursor.saturating_sub(1);
+        }
+        KeyCode::Down if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_cursor = (state.playlist_cursor + 1).min(state.playlists.len().saturating_sub(1));
+        }
+        //working with the videos of the open playlist
+        KeyCode::Up if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() => {
+            state.playlist_item_cursor = state.playlist_item_cursor.saturating_sub(1);
+        }
+        KeyCode::Down if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() => {
+            state.playlist_item_cursor = (state.playlist_item_cursor + 1).min(state.playlist_items.len().saturating_sub(1));
+        }
+        KeyCode::Enter
+            if state.active_menu_item == MenuItem::Playlists
+                && state.playlist_open.is_some()
+                && !state.playlist_selection_mode =>
+        {
+            if let Some(entry) = state.playlist_items.get(state.playlist_item_cursor).cloned() {
+                play_video(state, &entry.video_id, &entry.title, &entry.uploader, PlaySource::Playlist, 0);
+            }
+        }
+        KeyCode::Char('x') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() => {
+            let entry = state.playlist_items.get(state.playlist_item_cursor).cloned();
+            if let (Some(entry), Some(playlist)) = (entry, state.playlist_open.clone()) {
+                let result = if playlist.local {
+                    //the file may have been edited since the playlist was opened
+                    local_playlists::update(&playlist.id, |local| local.remove(&entry)).and_then(|removed| removed)
+                } else {
+                    state.youtube.remove_from_playlist(&entry.item_id).await
                 };
-                //putting the result of list_playlist into state.playlist if it returns data
-                if let Some(new) = maybe_playlists 
-                {
-                    state.playlists = new;
-                }
-
-                else 
-                {
-                    state.messages.push(" Failed to fetch playlists".to_string());
+                match result {
+                    Ok(()) => {
+                        state.status = format!("Removed {} from {}.", entry.title, playlist.title);
+                        load_playlist_items(state, playlist, true).await;
+                    }
+                    Err(e) => state.status = format!("Failed to remove {}: {}", entry.title, errors::describe(&e)),
                 }
             }
-            //b is for binding in multiple modes
-            KeyCode::Char('b') => 
-            {
-                //binding in playlist mode 
-                if state.active_menu_item == MenuItem::Playlists 
-                {
-                    state.active_menu_item = MenuItem::Playlists;
-                    state.playlist_selection_mode = true;
-                    state.playlist_number_input.clear();
-                }
-                //and in home mode for themes to use
-                if state.active_menu_item == MenuItem::Home 
-                {
-                    state.active_menu_item = MenuItem::Home;
-                    state.theme_selection_mode = true;
-                    state.playlist_number_input.clear();
+        }
+        //[ moves the highlighted video up, ] moves it down
+        KeyCode::Char(c @ '[') | KeyCode::Char(c @ ']')
+            if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() =>
+        {
+            let entry = state.playlist_items.get(state.playlist_item_cursor).cloned();
+            let last = state.playlist_items.len().saturating_sub(1);
+            let target = match (c, state.playlist_item_cursor) {
+                ('[', 0) => None,
+                ('[', cursor) => Some(cursor - 1),
+                (_, cursor) if cursor < last => Some(cursor + 1),
+                _ => None,
+            };
+            if let (Some(entry), Some(target), Some(playlist)) = (entry, target, state.playlist_open.clone()) {
+                //the position comes from youtube, deleted videos can leave gaps in the numbering
+                let other = state.playlist_items[target].clone();
+                let result = if playlist.local {
+                    local_playlists::update(&playlist.id, |local| local.swap(&entry, &other)).and_then(|swapped| swapped)
+                } else {
+                    state.youtube.move_playlist_item(&playlist.id, &entry, other.position).await
+                };
+                match result {
+                    Ok(()) => {
+                        state.playlist_item_cursor = target;
+                        load_playlist_items(state, playlist, true).await;
+                    }
+                    Err(e) => state.status = format!("Failed to move {}: {}", entry.title, errors::describe(&e)),
                 }
             }
-            //saving numbers for playlist selection
-            KeyCode::Char(digit) if state.playlist_selection_mode && digit.is_ascii_digit() => 
+        }
+        //rating the highlighted video
+        KeyCode::Char(c @ '+') | KeyCode::Char(c @ '-') | KeyCode::Char(c @ '=')
+            if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() =>
+        {
+            if let (Some(entry), SelectionAction::Rate(rating)) =
+                (state.playlist_items.get(state.playlist_item_cursor).cloned(), selection_action_for_key(c))
             {
-                if state.playlist_number_input.len() < 2 
-                {
-                    state.playlist_number_input.push(digit);
-                }
+                rate_video(state, entry.video_id, entry.title, rating).await;
             }
-
-            //saving numbers for theme selection
-            KeyCode::Char(digit) if state.theme_selection_mode && digit.is_ascii_digit() => 
+        }
+        KeyCode::Char('d') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() => {
+            if let Some(entry) = state.playlist_items.get(state.playlist_item_cursor).cloned() {
+                open_details(state, &entry.video_id).await;
+            }
+        }
+        //opening the channel of the highlighted video
+        KeyCode::Char('v') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() => {
+            if let Some(entry) = state.playlist_items.get(state.playlist_item_cursor).cloned() {
+                open_channel(state, &entry.channel_id).await;
+            }
+        }
+        KeyCode::Char('A')
+            if state.active_menu_item == MenuItem::Playlists
+                && state.playlist_open.as_ref().is_some_and(|playlist| playlist.local) =>
+        {
+            state.local_video_input = Some(String::new());
+        }
+        KeyCode::Esc if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_some() => {
+            state.playlist_open = None;
+            state.playlist_items.clear();
+        }
+        //opening the playlist dialogs
+        KeyCode::Char('v') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::Open));
+        }
+        KeyCode::Char('n') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::Create));
+        }
+        KeyCode::Char('N') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::CreateLocal));
+        }
+        KeyCode::Char('e') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::Rename));
+        }
+        KeyCode::Char('u') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::ChangePrivacy));
+        }
+        KeyCode::Char('d') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::Delete));
+        }
+        KeyCode::Char('E') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::Export));
+        }
+        KeyCode::Char('I') if state.active_menu_item == MenuItem::Playlists && state.playlist_open.is_none() => {
+            state.playlist_selection_mode = false;
+            state.playlist_form = Some(PlaylistForm::new(PlaylistAction::Import));
+        }
+        //b is for binding in multiple modes
+        KeyCode::Char('b') => 
+        {
+            //binding in playlist mode 
+            if state.active_menu_item == MenuItem::Playlists 
             {
-                if state.theme_number_input.len() < 2 
-                {
-                    state.theme_number_input.push(digit);
-                }
+                state.active_menu_item = MenuItem::Playlists;
+                state.playlist_selection_mode = true;
+                state.playlist_number_input.clear();
             }
-            //changing theme by pressing enter after specifying number
-            KeyCode::Enter if state.theme_selection_mode => 
+            //and in home mode for themes to use
+            if state.active_menu_item == MenuItem::Home 
             {
-                //parsing input
-                if let Ok(idx) = state.theme_number_input.parse::<usize>()
-                {   //seeing if number is smaller or equal to the number of themes present
-                    if idx > 0 && idx <= state.themes.len()
-                    {   //getting path and the themes 
-                        if let Some(path) = state.themes.get(idx - 1) 
-                        {
-                            match colors::load_theme_from_file(path) 
-                            {   //if theme selection is successful, state is updated
-                                Ok(new_theme) => 
-                                {
-                                    state.selected_theme = new_theme;
-                                    state.messages.push(format!("Theme {} loaded successfully.", path));
-                                }
-                                Err(e) => 
-                                {
-                                    state.messages.push(format!("Failed to load theme: {}", e));
-                                }
+                state.active_menu_item = MenuItem::Home;
+                state.theme_selection_mode = true;
+                state.playlist_number_input.clear();
+            }
+        }
+        //saving numbers for playlist selection
+        KeyCode::Char(digit)
+            if state.playlist_selection_mode
+                && digit.is_ascii_digit()
+                && state.playlist_number_input.len() < digits(state.playlists.len()) =>
+        {
+            state.playlist_number_input.push(digit);
+            if let Some(idx) = list_index(&state.playlist_number_input, state.playlists.len()) {
+                state.playlist_cursor = idx;
+            }
+        }
+
+        //saving numbers for theme selection
+        KeyCode::Char(digit)
+            if state.theme_selection_mode
+                && digit.is_ascii_digit()
+                && state.theme_number_input.len() < 2 =>
+        {
+            state.theme_number_input.push(digit);
+        }
+        //changing theme by pressing enter after specifying number
+        KeyCode::Enter if state.theme_selection_mode => 
+        {
+            //parsing input
+            if let Ok(idx) = state.theme_number_input.parse::<usize>()
+            {   //seeing if number is smaller or equal to the number of themes present
+                if idx > 0 && idx <= state.themes.len()
+                {   //getting path and the themes 
+                    if let Some(path) = state.themes.get(idx - 1) 
+                    {
+                        match colors::load_theme_from_file(path) 
+                        {   //if theme selection is successful, state is updated
+                            Ok(new_theme) => 
+                            {
+                                state.selected_theme = new_theme;
+                                state.messages.push(format!("Theme {} loaded successfully.", path));
+                            }
+                            Err(e) => 
+                            {
+                                state.messages.push(format!("Failed to load theme: {}", e));
                             }
                         }
-                        //error handling from here
-                        else 
-                        {
-                            state.messages.push("Theme not found.".to_string());
-                        }
-                    } 
+                    }
+                    //error handling from here
                     else 
                     {
-                        state.messages.push("Theme number out of range.".to_string());
+                        state.messages.push("Theme not found.".to_string());
                     }
                 } 
                 else 
                 {
-                    state.messages.push("Invalid number input.".to_string());
+                    state.messages.push("Theme number out of range.".to_string());
                 }
-                state.theme_selection_mode = false;
-                state.theme_number_input.clear();
+            } 
+            else 
+            {
+                state.messages.push("Invalid number input.".to_string());
             }
+            state.theme_selection_mode = false;
+            state.theme_number_input.clear();
+        }
 
-            //pretty much the same logic but for playlist selection 
-            KeyCode::Enter if state.playlist_selection_mode => 
+        //pretty much the same logic but for playlist selection 
+        KeyCode::Enter if state.playlist_selection_mode => 
+        {
+            //if the chars entered are numbers and can be mapped to a playlist, playback will start
+            if let Ok(idx) = state.playlist_number_input.parse::<usize>() 
             {
-                //if the chars entered are numbers and can be mapped to a playlist, playback will start
-                if let Ok(idx) = state.playlist_number_input.parse::<usize>() 
+                if idx > 0 && idx <= state.playlists.len() 
                 {
-                    if idx > 0 && idx <= state.playlists.len() 
+                    if let Some(playlist) = state.playlists.get(idx - 1) 
                     {
-                        if let Some(token) = get_token() 
-                        {
-                            if let Some(playlist) = state.playlists.get(idx - 1) 
-                            {
-                                let access_token_str = token.access_token().secret();
-                                play_playlist(access_token_str, &playlist.1).await?;
-                                state.messages.push(format!("Playing playlist: {}", playlist.0));
-                            }
-                            //else statements from here are error handling 
-                            else 
-                            {
-                                state.messages.push("Playlist not found.".to_string());
-                            }
-                        } 
-                        else 
+                        state.messages.push(format!("Playing playlist: {}", playlist.title));
+                        let result = if playlist.local {
+                            local_playlists::find(&playlist.id).and_then(|local| utilities::play_entries(&local.entries(), PlaySource::Playlist))
+                        } else {
+                            play_playlist(&state.youtube, &playlist.id, state.config.max_pages).await
+                        };
+                        if let Err(e) = result 
                         {
-                            state.messages.push("No valid token.".to_string());
+                            state.status = format!("Failed to play playlist: {}", errors::describe(&e));
                         }
-                    } 
+                    }
+                    //else statements from here are error handling 
                     else 
                     {
-                        state.messages.push("Playlist number out of range.".to_string());
+                        state.messages.push("Playlist not found.".to_string());
                     }
-                } else {
-                    state.messages.push("Invalid number input.".to_string());
+                } 
+                else 
+                {
+                    state.messages.push("Playlist number out of range.".to_string());
                 }
-                state.playlist_selection_mode = false;
-                state.playlist_number_input.clear();
+            } else {
+                state.messages.push("Invalid number input.".to_string());
             }
-            //Esc to stop playlist selection
-            KeyCode::Esc if state.playlist_selection_mode => 
-            {
-                state.playlist_selection_mode = false;
-                state.playlist_number_input.clear();
-                state.messages.push("Playlist selection cancelled.".to_string());
-            },
-            //do nothing on other key events
-            _ => {}
+            state.playlist_selection_mode = false;
+            state.playlist_number_input.clear();
+        }
+        //Esc to stop playlist selection
+        KeyCode::Esc if state.playlist_selection_mode => 
+        {
+            state.playlist_selection_mode = false;
+            state.playlist_number_input.clear();
+            state.messages.push("Playlist selection cancelled.".to_string());
         },
-        //do nothing on any other events
+        //do nothing on other key events
         _ => {}
     }
 
     Ok(false)
 }
 
+//fetches the videos of a playlist and shows them, force_refresh skips the cache after an edit
+async fn load_playlist_items(state: &mut AppState, playlist: Playlist, force_refresh: bool) {
+    let youtube = if force_refresh { state.youtube.force_refresh() } else { state.youtube.clone() };
+    let items = if playlist.local {
+        local_playlists::find(&playlist.id).map(|local| local.entries())
+    } else {
+        youtube.get_videos_from_playlist(&playlist.id, state.config.max_pages, |_, _| {}).await
+    };
+    match items {
+        Ok(items) => {
+            state.playlist_item_cursor = state.playlist_item_cursor.min(items.len().saturating_sub(1));
+            state.playlist_items = items;
+            state.playlist_open = Some(playlist);
+            load_ratings(state).await;
+        }
+        Err(e) => {
+            state.status = format!("Failed to load the videos of {}: {}", playlist.title, errors::describe(&e));
+        }
+    }
+}
+
+//loads a channel with its newest uploads and public playlists and shows its page
+async fn open_channel(state: &mut AppState, channel_id: &str) {
+    //deleted videos and some playlist items don't say who uploaded them
+    if channel_id.is_empty() {
+        state.status = "YouTube didn't say which channel this is from.".to_string();
+        return;
+    }
+    let channel = match state.youtube.channel(channel_id).await {
+        Ok(channel) => channel,
+        Err(e) => {
+            state.status = format!("Failed to load the channel: {}", errors::describe(&e));
+            return;
+        }
+    };
+    //one page of each is enough to browse, the newest uploads come first
+    let uploads = match state.youtube.get_videos_from_playlist(&channel.uploads, 1, |_, _| {}).await {
+        Ok(uploads) => uploads,
+        //a channel that never uploaded has no uploads playlist
+        Err(e) if matches!(e.downcast_ref::<ApiError>(), Some(ApiError::NotFound(_))) => vec![],
+        Err(e) => {
+            state.status = format!("Failed to load the uploads of {}: {}", channel.title, errors::describe(&e));
+            vec![]
+        }
+    };
+    let playlists = match state.youtube.channel_playlists(channel_id, 1).await {
+        Ok(playlists) => playlists,
+        Err(e) => {
+            state.status = format!("Failed to load the playlists of {}: {}", channel.title, errors::describe(&e));
+            vec![]
+        }
+    };
+    state.channel_open = Some(channel);
+    state.channel_uploads = uploads;
+    state.channel_playlists = playlists;
+    state.channel_upload_cursor = 0;
+    state.channel_playlist_cursor = 0;
+    state.channel_playlists_focused = false;
+    load_subscription_states(state).await;
+}
+
+//loads the description, statistics, tags and category of a video and shows them
+async fn open_details(state: &mut AppState, video_id: &str) {
+    match state.youtube.video(video_id).await {
+        Ok(details) => {
+            state.details_open = Some(details);
+            state.details_scroll = 0;
+            state.details_input.clear();
+        }
+        Err(e) => state.status = format!("Failed to load the video details: {}", errors::describe(&e)),
+    }
+}
+
+//loads the first page of comments of a video and shows them
+async fn open_comments(state: &mut AppState, video_id: String, video_title: String, order: CommentOrder) {
+    match state.youtube.comment_threads(&video_id, order, None).await {
+        Ok(page) => state.comments = Some(CommentsView::new(video_id, video_title, order, page)),
+        Err(e) => state.status = format!("Failed to load the comments: {}", errors::describe(&e)),
+    }
+}
+
+//how many digits the highest number of a list has, so the number input can reach every item
+fn digits(len: usize) -> usize {
+    len.max(1).to_string().len()
+}
+
+//the index a typed number points to, if it is a number of the list
+fn list_index(input: &str, len: usize) -> Option<usize> {
+    input.parse::<usize>().ok().filter(|number| (1..=len).contains(number)).map(|number| number - 1)
+}
+
+//plays a single video, then autoplay keeps going with related tracks
+//mpv runs inside raw mode, so a failed play is reported in the status bar
+fn play_video(state: &mut AppState, video_id: &str, title: &str, uploader: &str, source: PlaySource, start_secs: u64) {
+    match utilities::play_song_by_id_from(video_id, title, uploader, source, start_secs) {
+        Ok(()) => {
+            state.messages.push(format!("Playing video {}", video_id));
+            autoplay(state, video_id);
+        }
+        Err(e) => state.status = format!("Failed to play video: {}", errors::describe(&e)),
+    }
+}
+
+//after a single video, autoplay keeps going with related tracks
+fn autoplay(state: &mut AppState, video_id: &str) {
+    if state.autoplay {
+        state.status = match utilities::play_radio(video_id, state.config.autoplay_tracks) {
+            Ok(ended) => ended.to_string(),
+            Err(e) => format!("Autoplay stopped: {}", errors::describe(&e)),
+        };
+        //the radio tracks are in the history now
+        if let Ok(entries) = history::load_history() {
+            state.history = entries;
+        }
+    }
+}
+
+fn save_seen(state: &mut AppState) {
+    if let Err(e) = seen::save_seen(&state.seen) {
+        state.status = format!("Failed to save seen videos: {}", e);
+    }
+}
+
+//the keys that start a selection in search and history, v, u and w only in search
+fn selection_action_for_key(key: char) -> SelectionAction {
+    match key {
+        'l' => SelectionAction::AddToPlaylist,
+        '+' => SelectionAction::Rate(Rating::Like),
+        '-' => SelectionAction::Rate(Rating::Dislike),
+        '=' => SelectionAction::Rate(Rating::Unrated),
+        'v' => SelectionAction::OpenChannel,
+        'u' => SelectionAction::ToggleSubscription,
+        'w' => SelectionAction::SaveForLater,
+        'd' => SelectionAction::ShowDetails,
+        _ => SelectionAction::Play,
+    }
+}
+
+async fn rate_video(state: &mut AppState, video_id: String, title: String, rating: Rating) {
+    match state.youtube.rate_video(&video_id, rating).await {
+        Ok(()) => {
+            state.status = match rating {
+                Rating::Like => format!("Liked {}.", title),
+                Rating::Dislike => format!("Disliked {}.", title),
+                Rating::Unrated => format!("Removed your rating of {}.", title),
+            };
+            state.ratings.insert(video_id, rating);
+        }
+        Err(e) => state.status = format!("Failed to rate {}: {}", title, errors::describe(&e)),
+    }
+}
+
+//asks youtube how the user rated the videos in the search results and the open playlist
+//videos whose rating is already known are skipped
+async fn load_ratings(state: &mut AppState) {
+    //ratings belong to an account, without one there is nothing to show
+    if state.youtube.access() != Access::Account {
+        return;
+    }
+    let search_ids = state.search_result.iter().filter_map(|result| match result {
+        SearchResult::Video(video) => Some(video.id.clone()),
+        _ => None,
+    });
+    let item_ids = state.playlist_items.iter().map(|entry| entry.video_id.clone());
+    let mut video_ids: Vec<String> = search_ids
+        .chain(item_ids)
+        .filter(|id| !state.ratings.contains_key(id))
+        .collect();
+    video_ids.sort();
+    video_ids.dedup();
+    if video_ids.is_empty() {
+        return;
+    }
+    match state.youtube.get_ratings(&video_ids).await {
+        Ok(ratings) => state.ratings.extend(ratings),
+        Err(e) => state.status = format!("Failed to load your ratings: {}", errors::describe(&e)),
+    }
+}
+
+//asks youtube which of the channels behind the search results and the open channel the user is subscribed to
+//channels whose state is already known are skipped
+async fn load_subscription_states(state: &mut AppState) {
+    if state.youtube.access() != Access::Account {
+        return;
+    }
+    let search_ids = state.search_result.iter().map(|result| result.channel().0.to_string());
+    let channel_ids = state.channel_open.iter().map(|channel| channel.id.clone());
+    let mut ids: Vec<String> = search_ids
+        .chain(channel_ids)
+        .filter(|id| !id.is_empty() && !state.subscribed.contains_key(id))
+        .collect();
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() {
+        return;
+    }
+    match state.youtube.subscription_ids(&ids).await {
+        Ok(mut subscribed) => {
+            for id in ids {
+                let subscription = subscribed.remove(&id);
+                state.subscribed.insert(id, subscription);
+            }
+        }
+        Err(e) => state.status = format!("Failed to load your subscriptions: {}", errors::describe(&e)),
+    }
+}
+
+//subscribes to the channel, or unsubscribes if the user already is
+async fn toggle_subscription(state: &mut AppState, channel_id: String, title: String) {
+    if channel_id.is_empty() {
+        state.status = "YouTube didn't say which channel this is from.".to_string();
+        return;
+    }
+    if !state.subscribed.contains_key(&channel_id) {
+        load_subscription_states(state).await;
+    }
+    match state.subscribed.get(&channel_id).cloned().flatten() {
+        Some(subscription_id) => match state.youtube.unsubscribe(&subscription_id).await {
+            Ok(()) => {
+                state.status = format!("Unsubscribed from {}.", title);
+                state.subscriptions.retain(|subscription| subscription.channel_id != channel_id);
+                state.subscribed.insert(channel_id, None);
+            }
+            Err(e) => state.status = format!("Failed to unsubscribe from {}: {}", title, errors::describe(&e)),
+        },
+        None => match state.youtube.subscribe(&channel_id).await {
+            Ok(subscription) => {
+                state.status = format!("Subscribed to {}.", title);
+                state.subscribed.insert(channel_id, Some(subscription.id.clone()));
+                //kept in the same alphabetical order youtube lists them in
+                state.subscriptions.push(subscription);
+                state.subscriptions.sort_by_key(|subscription| subscription.title.to_lowercase());
+            }
+            Err(e) => state.status = format!("Failed to subscribe to {}: {}", title, errors::describe(&e)),
+        },
+    }
+}
+
+//adds the video to the save for later playlist from the config, creating that playlist the first time
+async fn save_for_later(state: &mut AppState, video: LocalVideo) {
+    let wanted = state.config.save_for_later_playlist.trim().to_string();
+    if wanted.is_empty() {
+        state.status = "Set save_for_later_playlist in config.json to save videos for later.".to_string();
+        return;
+    }
+    //the list is only trusted once fully loaded, otherwise the playlist may be missing from it and be made twice
+    let playlists = if !state.playlists_loaded {
+        let mut playlists: Vec<Playlist> = match local_playlists::load_local_playlists() {
+            Ok(local) => local.iter().map(LocalPlaylist::to_playlist).collect(),
+            Err(e) => {
+                state.status = format!("Failed to load local playlists: {}", e);
+                return;
+            }
+        };
+        //a local save for later playlist doesn't need youtube at all
+        if !playlists.iter().any(|playlist| is_save_for_later(playlist, &wanted)) {
+            match state.youtube.list_playlists(state.config.max_pages, |_, _| {}).await {
+                Ok(own) => playlists.extend(own),
+                Err(e) => {
+                    state.status = format!("Failed to load your playlists: {}", errors::describe(&e));
+                    return;
+                }
+            }
+        }
+        playlists
+    } else {
+        state.playlists.clone()
+    };
+    //youtube's own playlists, like Watch later, can't be written to through the api
+    let existing = playlists
+        .into_iter()
+        .find(|playlist| !playlist.system && is_save_for_later(playlist, &wanted));
+    let (playlist, created) = match existing {
+        Some(playlist) => (playlist, false),
+        None => match state.youtube.create_playlist(&wanted, "Videos saved for later.", "private").await {
+            Ok(playlist) => (playlist, true),
+            Err(e) => {
+                state.status = format!("Failed to create the playlist {}: {}", wanted, errors::describe(&e));
+                return;
+            }
+        },
+    };
+    let title = video.title.clone();
+    match add_to(state, &playlist, video).await {
+        Ok(()) if created => state.status = format!("Created {} and saved {} to it.", playlist.title, title),
+        Ok(()) => state.status = format!("Saved {} to {}.", title, playlist.title),
+        Err(e) => state.status = format!("Failed to save {} for later: {}", title, errors::describe(&e)),
+    }
+    if created {
+        state.playlists.clear();
+        state.playlists_total = 0;
+        state.playlists_generation += 1;
+        state.playlists_requested = true;
+        state.playlists_force_refresh = true;
+    }
+}
+
+//whether the playlist is the one the save_for_later_playlist setting names, by id or by title
+pub fn is_save_for_later(playlist: &Playlist, wanted: &str) -> bool {
+    playlist.id == wanted || playlist.title.eq_ignore_ascii_case(wanted)
+}
+
+//asks which playlist the video goes to, the playlists are loaded first if they aren't yet
+fn start_add_to_playlist(state: &mut AppState, video: LocalVideo) {
+    if state.playlists.is_empty() && !state.playlists_loading {
+        state.playlists_generation += 1;
+        state.playlists_requested = true;
+    }
+    state.add_to_playlist = Some(PendingAdd { video, input: String::new() });
+}
+
+//adds a video to the end of a youtube or local playlist
+async fn add_to(state: &AppState, playlist: &Playlist, video: LocalVideo) -> Result<()> {
+    if playlist.local {
+        local_playlists::update(&playlist.id, |local| local.videos.push(video))
+    } else {
+        state.youtube.add_to_playlist(&playlist.id, &video.video_id).await
+    }
+}
+
+//adds any video to the end of a local playlist, the title and uploader are looked up if youtube can be asked
+//without an account only the id is kept
+async fn add_video_by_id(state: &mut AppState, playlist: Playlist, video_id: String) {
+    let video = match state.youtube.video_details(std::slice::from_ref(&video_id)).await {
+        Ok(videos) if !videos.is_empty() => local_video(&videos[0]),
+        Ok(_) => {
+            state.status = format!("{} is unavailable or doesn't exist.", video_id);
+            return;
+        }
+        Err(_) => LocalVideo { video_id, title: String::new(), uploader: String::new(), channel_id: String::new() },
+    };
+    let title = if video.title.is_empty() { video.video_id.clone() } else { video.title.clone() };
+    match add_to(state, &playlist, video).await {
+        Ok(()) => {
+            state.status = format!("Added {} to {}.", title, playlist.title);
+            load_playlist_items(state, playlist, true).await;
+            state.playlist_item_cursor = state.playlist_items.len().saturating_sub(1);
+        }
+        Err(e) => state.status = format!("Failed to add {} to {}: {}", title, playlist.title, errors::describe(&e)),
+    }
+}
+
+//what a local playlist keeps of a search result
+fn local_video(video: &Video) -> LocalVideo {
+    LocalVideo {
+        video_id: video.id.clone(),
+        title: video.title.clone(),
+        uploader: video.uploader.clone(),
+        channel_id: video.channel_id.clone(),
+    }
+}
+
+//sends the finished playlist dialog to youtube, or saves it for local playlists, and reloads the playlists if it worked
+async fn apply_playlist_form(state: &mut AppState, form: PlaylistForm) {
+    let local = form.action == PlaylistAction::CreateLocal || form.target.as_ref().is_some_and(|playlist| playlist.local);
+    let result = match (form.action, form.target) {
+        (PlaylistAction::CreateLocal, _) => {
+            let playlist = LocalPlaylist::new(&form.title, &form.description);
+            let message = format!("Created local playlist {}.", playlist.title);
+            local_playlists::load_local_playlists()
+                .and_then(|mut playlists| {
+                    playlists.push(playlist);
+                    local_playlists::save_local_playlists(&playlists)
+                })
+                .map(|_| message)
+        }
+        (PlaylistAction::Rename, Some(playlist)) if playlist.local => {
+            let title = form.title;
+            let message = format!("Renamed {} to {}.", playlist.title, title);
+            local_playlists::update(&playlist.id, |local| local.title = title).map(|_| message)
+        }
+        (PlaylistAction::Delete, Some(playlist)) if playlist.local => {
+            local_playlists::delete(&playlist.id).map(|_| format!("Deleted local playlist {}.", playlist.title))
+        }
+        (PlaylistAction::Create, _) => state
+            .youtube
+            .create_playlist(&form.title, &form.description, PRIVACY_OPTIONS[form.privacy])
+            .await
+            .map(|playlist| format!("Created playlist {}.", playlist.title)),
+        (PlaylistAction::Rename, Some(mut playlist)) => {
+            let old_title = std::mem::replace(&mut playlist.title, form.title);
+            state
+                .youtube
+                .update_playlist(&playlist)
+                .await
+                .map(|_| format!("Renamed {} to {}.", old_title, playlist.title))
+        }
+        (PlaylistAction::ChangePrivacy, Some(mut playlist)) => {
+            playlist.privacy = PRIVACY_OPTIONS[form.privacy].to_string();
+            state
+                .youtube
+                .update_playlist(&playlist)
+                .await
+                .map(|_| format!("{} is now {}.", playlist.title, playlist.privacy))
+        }
+        //opening doesn't change anything on youtube
+        (PlaylistAction::Open, Some(playlist)) => {
+            state.playlist_item_cursor = 0;
+            load_playlist_items(state, playlist, false).await;
+            return;
+        }
+        (PlaylistAction::Delete, Some(playlist)) => state
+            .youtube
+            .delete_playlist(&playlist.id)
+            .await
+            .map(|_| format!("Deleted playlist {}.", playlist.title)),
+        //exporting doesn't change anything on youtube either
+        (PlaylistAction::Export, Some(playlist)) => {
+            state.status = match export_playlist(state, &playlist, &form.path).await {
+                Ok(message) => message,
+                Err(e) => format!("{} failed: {}", form.action.label(), errors::describe(&e)),
+            };
+            return;
+        }
+        //importing either adds to the picked playlist or creates one
+        (PlaylistAction::Import, target) => import_playlist(state, &form.path, target).await,
+        //every other action picks a playlist first
+        (_, None) => return,
+    };
+
+    match result {
+        Ok(message) => {
+            state.status = message;
+            //the cached list is out of date now
+            state.playlists.clear();
+            state.playlists_total = 0;
+            state.playlists_generation += 1;
+            state.playlists_requested = true;
+            //youtube's list only has to be fetched again if something changed there
+            state.playlists_force_refresh = !local;
+        }
+        Err(e) => {
+            state.status = format!("{} failed: {}", form.action.label(), errors::describe(&e));
+        }
+    }
+}
+
+//writes the videos of a playlist to a file, with the durations m3u and csv files carry
+async fn export_playlist(state: &mut AppState, playlist: &Playlist, path: &str) -> Result<String> {
+    let items = if playlist.local {
+        local_playlists::find(&playlist.id)?.entries()
+    } else {
+        state
+            .youtube
+            .get_videos_from_playlist(&playlist.id, state.config.max_pages, |_, _| {})
+            .await?
+    };
+    //playlist items don't have durations, those come from the video details
+    //local playlists are exported without them when youtube can't be asked
+    let video_ids: Vec<String> = items.iter().map(|item| item.video_id.clone()).collect();
+    let durations: HashMap<String, Option<u64>> = match state.youtube.video_details(&video_ids).await {
+        Ok(videos) => videos
+            .into_iter()
+            .map(|video| (video.id, playlist_files::duration_secs(&video.duration)))
+            .collect(),
+        Err(_) if playlist.local => HashMap::new(),
+        Err(e) => return Err(e),
+    };
+
+    let videos: Vec<FileEntry> = items
+        .into_iter()
+        .map(|item| FileEntry {
+            duration: durations.get(&item.video_id).copied().flatten(),
+            url: playlist_files::watch_url(&item.video_id),
+            video_id: item.video_id,
+            title: item.title,
+            uploader: item.uploader,
+        })
+        .collect();
+    let count = videos.len();
+    playlist_files::export(Path::new(path), &PlaylistFile { title: Some(playlist.title.clone()), videos })?;
+    Ok(format!("Exported {} videos of {} to {}.", count, playlist.title, path))
+}
+
+//adds the videos of a file to target, or to a new private playlist named after the file
+//entries without a link are searched for by their title, the ones that can't be found,
+//are unavailable or fail to be added are listed on the account tab
+async fn import_playlist(state: &mut AppState, path: &str, target: Option<Playlist>) -> Result<String> {
+    let file = playlist_files::import(Path::new(path))?;
+    if file.videos.is_empty() {
+        bail!("No videos found in {}", path);
+    }
+
+    //the number of the entry in the file and what went wrong with it
+    let mut skipped: Vec<(usize, String)> = vec![];
+    let mut resolved: Vec<(usize, String)> = vec![];
+    for (idx, entry) in file.videos.iter().enumerate() {
+        if let Some(video_id) = entry.resolved_id() {
+            resolved.push((idx, video_id));
+            continue;
+        }
+        if entry.title.is_empty() {
+            skipped.push((idx, format!("{} isn't a YouTube video", entry.describe())));
+            continue;
+        }
+        //the best match of a search for the title, a search costs as much as a hundred reads
+        match state.youtube.search(&entry.describe(), &SearchFilters::default(), None).await {
+            Ok(page) => match page.results.into_iter().find_map(|result| match result {
+                SearchResult::Video(video) => Some(video.id),
+                _ => None,
+            }) {
+                Some(video_id) => resolved.push((idx, video_id)),
+                None => skipped.push((idx, format!("nothing found for {}", entry.describe()))),
+            },
+            Err(e) => skipped.push((idx, format!("searching for {} failed: {}", entry.describe(), errors::describe(&e)))),
+        }
+    }
+
+    //removed and private videos have no details
+    //local playlists keep the ids without titles when youtube can't be asked, like an export does
+    let video_ids: Vec<String> = resolved.iter().map(|(_, video_id)| video_id.clone()).collect();
+    let available: HashMap<String, LocalVideo> = match state.youtube.video_details(&video_ids).await {
+        Ok(videos) => videos.iter().map(|video| (video.id.clone(), local_video(video))).collect(),
+        Err(_) if target.as_ref().is_some_and(|playlist| playlist.local) => video_ids
+            .iter()
+            .map(|video_id| {
+                let video = LocalVideo {
+                    video_id: video_id.clone(),
+                    title: String::new(),
+                    uploader: String::new(),
+                    channel_id: String::new(),
+                };
+                (video_id.clone(), video)
+            })
+            .collect(),
+        Err(e) => return Err(e),
+    };
+    resolved.retain(|(idx, video_id)| {
+        let is_available = available.contains_key(video_id);
+        if !is_available {
+            skipped.push((*idx, format!("{} is unavailable", file.videos[*idx].describe())));
+        }
+        is_available
+    });
+
+    let total = file.videos.len();
+    let result = if resolved.is_empty() {
+        Err(anyhow::anyhow!("none of the {} videos in {} could be found", total, path))
+    } else {
+        let playlist = match target {
+            Some(playlist) => playlist,
+            None => {
+                let title = file.title.clone().unwrap_or_else(|| {
+                    Path::new(path)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.to_string())
+                });
+                state
+                    .youtube
+                    .create_playlist(&title, &format!("Imported from {}", path), "private")
+                    .await?
+            }
+        };
+        let mut added = 0;
+        for (position, (idx, video_id)) in resolved.iter().enumerate() {
+            //the rest would most likely fail the same way, quota running out being the usual reason
+            if let Err(e) = add_to(state, &playlist, available[video_id].clone()).await {
+                skipped.push((*idx, format!("adding failed: {}", errors::describe(&e))));
+                skipped.extend(
+                    resolved[position + 1..]
+                        .iter()
+                        .map(|(idx, _)| (*idx, format!("{} wasn't added", file.videos[*idx].describe()))),
+                );
+                break;
+            }
+            added += 1;
+        }
+        Ok(format!("Imported {} of {} videos into {}.", added, total, playlist.title))
+    };
+
+    if !skipped.is_empty() {
+        skipped.sort_by_key(|(idx, _)| *idx);
+        state.messages.push(format!("Skipped while importing {}:", path));
+        state
+            .messages
+            .extend(skipped.iter().map(|(idx, reason)| format!("  entry {}: {}", idx + 1, reason)));
+    }
+    result.map(|message| match skipped.len() {
+        0 => message,
+        count => format!("{} {} skipped, listed on the account tab.", message, count),
+    })
+}
+
 //loads theme from file
 pub fn load_and_set_theme_from_file(path: &str) -> Result<Theme> {
     let json = fs::read_to_string(path)?;
@@ -377,7 +1848,7 @@ pub fn load_and_set_theme_from_file(path: &str) -> Result<Theme> {
     Ok(theme)
 }
 
-pub fn set_token(token: OAuthToken) {
+pub fn set_token(token: SavedToken) {
     OAUTH_TOKEN
         .get_or_init(|| Mutex::new(None))
         .lock()
@@ -386,7 +1857,66 @@ pub fn set_token(token: OAuthToken) {
 }
 
 pub fn get_token() -> Option<OAuthToken> {
-    OAUTH_TOKEN.get()?.lock().unwrap().clone()
+    Some(OAUTH_TOKEN.get()?.lock().unwrap().as_ref()?.token.clone())
+}
+
+//identifies the logged in account for the response cache
+//the refresh token stays the same for a login, so a hash of it is enough
+pub fn account_key() -> String {
+    let refresh_token = OAUTH_TOKEN.get().and_then(|cell| {
+        cell.lock()
+            .unwrap()
+            .as_ref()?
+            .token
+            .refresh_token()
+            .map(|token| token.secret().to_string())
+    });
+    match refresh_token {
+        Some(token) => format!("{:016x}", cache::stable_hash(&[&token])),
+        None => "anonymous".to_string(),
+    }
+}
+
+//the token source of the client, the account signed in with 'a'
+pub struct SignedInAccount;
+
+impl TokenSource for SignedInAccount {
+    fn token(&self) -> Option<OAuthToken> {
+        get_token()
+    }
+
+    fn refresh(&self) -> BoxFuture<'_, Result<OAuthToken>> {
+        Box::pin(refresh_and_set_token())
+    }
+
+    fn account_key(&self) -> String {
+        account_key()
+    }
+}
+
+//true if there is a token and it is about to expire
+pub fn token_needs_refresh() -> bool {
+    let margin = chrono::Duration::minutes(TOKEN_REFRESH_MARGIN_MINUTES);
+    OAUTH_TOKEN
+        .get()
+        .and_then(|cell| cell.lock().unwrap().as_ref().map(|token| token.expires_within(margin)))
+        .unwrap_or(false)
+}
+
+//refreshes the token and puts the new one into OAUTH_TOKEN
+//if another refresh finished while we waited for the lock, its token is used instead
+pub async fn refresh_and_set_token() -> Result<OAuthToken> {
+    let before = get_token().map(|token| token.access_token().secret().to_string());
+    let _guard = REFRESH_LOCK.lock().await;
+    if let Some(current) = get_token() {
+        if Some(current.access_token().secret().to_string()) != before {
+            return Ok(current);
+        }
+    }
+    let saved = authenticate::refresh_token().await?;
+    let token = saved.token.clone();
+    set_token(saved);
+    Ok(token)
 }
 
 pub fn load_and_set_token() -> anyhow::Result<()> {
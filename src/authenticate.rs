@@ -1,25 +1,56 @@
 //this is responsible for authentication, from reading in credentials to returning a token
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
 use oauth2::basic::BasicClient;
 use oauth2::reqwest::async_http_client;
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl, RefreshToken,
     Scope, StandardTokenResponse, TokenResponse, TokenUrl,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::BufReader;
 use std::path::Path;
 use tiny_http::{Response, Server};
 use url::Url;
-use webbrowser;
 
 pub type OAuthToken = StandardTokenResponse<oauth2::EmptyExtraTokenFields, oauth2::basic::BasicTokenType>;
 
+//the token as it is kept in token.json, with the time it was issued so we know when it expires
+//token.json files from older versions have no issued_at, those are treated as expired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedToken {
+    #[serde(flatten)]
+    pub token: OAuthToken,
+    #[serde(default)]
+    pub issued_at: Option<DateTime<Utc>>,
+}
+
+impl SavedToken {
+    pub fn new(token: OAuthToken) -> Self {
+        Self {
+            token,
+            issued_at: Some(Utc::now()),
+        }
+    }
+
+    //true if the access token expires in less than margin, or if we can't tell
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        match (self.issued_at, self.token.expires_in()) {
+            (Some(issued_at), Some(expires_in)) => match Duration::from_std(expires_in) {
+                Ok(expires_in) => issued_at + expires_in - margin <= Utc::now(),
+                Err(_) => false,
+            },
+            _ => true,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Installed {
     pub client_id: String,
     pub client_secret: String,
+    #[allow(dead_code)]
     pub redirect_uris: Vec<String>,
 }
 
@@ -36,22 +67,20 @@ pub fn read_credentials(path: &str) -> Result<Credentials> {
 }
 
 //writes out access token, you may need to delete "token.json" after not starting the app in a while
-pub fn save_token(token: &OAuthToken) -> Result<()> {
+pub fn save_token(token: &SavedToken) -> Result<()> {
     let json = serde_json::to_string_pretty(token)?;
     fs::write("token.json", json)?;
     Ok(())
 }
 //reading in access token 
-pub fn load_token() -> Result<OAuthToken> {
+pub fn load_token() -> Result<SavedToken> {
     let data = fs::read_to_string("token.json")?;
-    let token: OAuthToken = serde_json::from_str(&data)?;
+    let token: SavedToken = serde_json::from_str(&data)?;
     Ok(token)
 }
 
-//whoo booooy here we go
-pub async fn authenticate<F>(mut display_message: F) -> Result<OAuthToken> where F: FnMut(&str),
-    {
-    //initializing values for authentication, like client id and secret and an urls    
+//creating the oauth client from the values in credentials.json
+fn oauth_client() -> Result<BasicClient> {
     let creds = read_credentials("credentials.json")?;
     let client_id = ClientId::new(creds.installed.client_id.clone());
     let client_secret = ClientSecret::new(creds.installed.client_secret.clone());
@@ -63,26 +92,50 @@ pub async fn authenticate<F>(mut display_message: F) -> Result<OAuthToken> where
     //creating Client object from values and setting redirect url    
     let client = BasicClient::new(client_id, Some(client_secret), auth_url, Some(token_url))
         .set_redirect_uri(RedirectUrl::new(redirect_uri.to_string())?);
+    Ok(client)
+}
+
+//exchanges the refresh token in token.json for a new access token and saves it
+pub async fn refresh_token() -> Result<SavedToken> {
+    let client = oauth_client()?;
+    let saved = load_token()?;
+    let refresh_token = saved
+        .token
+        .refresh_token()
+        .ok_or_else(|| anyhow!("token.json has no refresh token, please authenticate again"))?;
+
+    let mut new_token = client
+        .exchange_refresh_token(&RefreshToken::new(refresh_token.secret().to_string()))
+        .request_async(async_http_client)
+        .await?;
+    //google only sends a refresh token on the first login, so the old one is kept
+    if new_token.refresh_token().is_none() {
+        new_token.set_refresh_token(Some(refresh_token.clone()));
+    }
+
+    let new_token = SavedToken::new(new_token);
+    save_token(&new_token)?;
+    Ok(new_token)
+}
+
+//whoo booooy here we go
+pub async fn authenticate<F>(mut display_message: F) -> Result<SavedToken> where F: FnMut(&str),
+    {
+    //initializing values for authentication, like client id and secret and an urls    
+    let client = oauth_client()?;
 
     // Try refreshing existing token if available
-    if Path::new("token.json").exists() 
+    if Path::new("token.json").exists() && load_token()?.token.refresh_token().is_some()
     {
-        let token = load_token()?;
-        if let Some(refresh_token) = token.refresh_token() 
-        {
-            let new_token = client
-                .exchange_refresh_token(&RefreshToken::new(refresh_token.secret().to_string()))
-                .request_async(async_http_client).await?;
-            save_token(&new_token)?;
-            display_message("Token refreshed successfully.");
-            return Ok(new_token);
-        }
+        let new_token = refresh_token().await?;
+        display_message("Token refreshed successfully.");
+        return Ok(new_token);
     }
     //if no token.json is present, full authorization flow begins
 
     //build authorization URL, adding csrf token to prevent forgery, adding scope, and a consent form 
     let (auth_url, _csrf_token) = client
-        .authorize_url(|| CsrfToken::new_random())
+        .authorize_url(CsrfToken::new_random)
         .add_scope(Scope::new(
             "https://www.googleapis.com/auth/youtube".to_string(),
         ))
@@ -122,6 +175,7 @@ pub async fn authenticate<F>(mut display_message: F) -> Result<OAuthToken> where
         .exchange_code(AuthorizationCode::new(code))
         .request_async(async_http_client)
         .await?;
+    let token = SavedToken::new(token);
     save_token(&token)?;
     display_message("Authentication complete!");
 
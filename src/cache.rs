@@ -0,0 +1,124 @@
+//cache keeps the bodies of API responses on disk so the same request doesn't cost quota twice
+//entries are keyed by the account and the full request url, and remember the ETag google sent
+//so a stale entry can be revalidated with If-None-Match instead of downloaded again
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CACHE_DIR: &str = "cache";
+
+//entries that weren't fetched or revalidated for this long are deleted when the tui starts
+const MAX_AGE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub body: String,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CacheEntry {
+    //fresh entries are used without asking youtube at all
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        Utc::now() - self.fetched_at < ttl
+    }
+}
+
+//64 bit FNV-1a of the parts, unlike DefaultHasher it gives the same value on every rust version
+//so file names stay the same after an update, a zero byte ends every part so ("ab", "c") and ("a", "bc") differ
+pub fn stable_hash(parts: &[&str]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    parts
+        .iter()
+        .flat_map(|part| part.bytes().chain(std::iter::once(0)))
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+//one file per entry in dir, named after a hash of the account and the url
+fn entry_path(dir: &Path, account: &str, url: &str) -> PathBuf {
+    dir.join(format!("{:016x}.json", stable_hash(&[account, url])))
+}
+
+pub fn load(dir: &Path, account: &str, url: &str) -> Option<CacheEntry> {
+    let json = fs::read_to_string(entry_path(dir, account, url)).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&json).ok()?;
+    //a hash collision would hand back another request's body
+    if entry.url == url {
+        Some(entry)
+    } else {
+        None
+    }
+}
+
+pub fn store(dir: &Path, account: &str, entry: &CacheEntry) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(entry_path(dir, account, &entry.url), serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+//deletes the entries that are too old to be worth revalidating, and files that aren't entries
+//returns how many files were deleted
+pub fn evict_expired(dir: &Path) -> Result<usize> {
+    evict_in(dir, Duration::days(MAX_AGE_DAYS))
+}
+
+fn evict_in(dir: &Path, max_age: Duration) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut evicted = 0;
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let entry = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<CacheEntry>(&json).ok());
+        if !entry.is_some_and(|entry| entry.is_fresh(max_age)) {
+            fs::remove_file(&path)?;
+            evicted += 1;
+        }
+    }
+    Ok(evicted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash_does_not_change() {
+        //the file names of existing cache entries depend on these values
+        assert_eq!(stable_hash(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash(&["anonymous", "https://example.com/videos?id=1"]), 0xf6bf_1c65_5558_5945);
+        assert_ne!(stable_hash(&["ab", "c"]), stable_hash(&["a", "bc"]));
+    }
+
+    #[test]
+    fn old_and_broken_entries_are_evicted() {
+        let dir = std::env::temp_dir().join(format!("yt_tui_cache_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, age: Duration| {
+            let entry = CacheEntry {
+                url: name.to_string(),
+                etag: None,
+                body: "{}".to_string(),
+                fetched_at: Utc::now() - age,
+            };
+            fs::write(dir.join(name), serde_json::to_string(&entry).unwrap()).unwrap();
+        };
+        write("recent.json", Duration::days(1));
+        write("old.json", Duration::days(8));
+        fs::write(dir.join("broken.json"), "not json").unwrap();
+
+        assert_eq!(evict_in(&dir, Duration::days(7)).unwrap(), 2);
+        let left: Vec<_> = fs::read_dir(&dir).unwrap().map(|file| file.unwrap().file_name()).collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(left, ["recent.json"]);
+    }
+}
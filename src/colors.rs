@@ -17,7 +17,9 @@ impl<'de> Deserialize<'de> for MyColor
     }
 
 //theme has colors for most ui components, allowing for customization
+//some colors are part of the theme format but aren't drawn by any widget yet
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct Theme 
 {
     pub tui_lines: MyColor,
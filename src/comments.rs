@@ -0,0 +1,65 @@
+//comments keeps what the comments view shows for one video
+//threads are loaded a page at a time, replies are only shown for the threads the user expanded
+use crate::youtube::{CommentOrder, CommentPage, CommentThread};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct CommentsView {
+    pub video_id: String,
+    pub video_title: String,
+    pub threads: Vec<CommentThread>,
+    pub order: CommentOrder,
+    //None once the last page is loaded
+    pub next_page: Option<String>,
+    //the highlighted thread
+    pub cursor: usize,
+    //ids of the threads whose replies are shown
+    pub expanded: HashSet<String>,
+}
+
+impl CommentsView {
+    pub fn new(video_id: String, video_title: String, order: CommentOrder, page: CommentPage) -> Self {
+        Self {
+            video_id,
+            video_title,
+            threads: page.threads,
+            order,
+            next_page: page.next_page_token,
+            cursor: 0,
+            expanded: HashSet::new(),
+        }
+    }
+
+    //adds the threads of the next page after the ones already shown
+    pub fn append(&mut self, page: CommentPage) {
+        self.threads.extend(page.threads);
+        self.next_page = page.next_page_token;
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        self.cursor = if down {
+            (self.cursor + 1).min(self.threads.len().saturating_sub(1))
+        } else {
+            self.cursor.saturating_sub(1)
+        };
+    }
+
+    pub fn highlighted(&self) -> Option<&CommentThread> {
+        self.threads.get(self.cursor)
+    }
+
+    //true if the highlighted thread has replies that weren't sent with it
+    pub fn needs_replies(&self) -> bool {
+        self.highlighted()
+            .is_some_and(|thread| !self.expanded.contains(&thread.id) && thread.replies.len() < thread.reply_count as usize)
+    }
+
+    //shows or hides the replies of the highlighted thread
+    pub fn toggle_replies(&mut self) {
+        if let Some(id) = self.highlighted().map(|thread| thread.id.clone()) {
+            if !self.expanded.remove(&id) {
+                self.expanded.insert(id);
+            }
+        }
+    }
+}
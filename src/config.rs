@@ -0,0 +1,100 @@
+//config holds the user adjustable settings of the tui
+//it is read from config.json next to credentials.json, every field is optional
+use crate::cache::CACHE_DIR;
+use crate::quota::{QuotaLimits, QUOTA_FILE};
+use crate::retry::RetryPolicy;
+use crate::youtube::DEFAULT_BASE_URL;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+pub const CONFIG_FILE: &str = "config.json";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    //how many pages of 50 items are fetched at most for playlists and playlist items
+    pub max_pages: usize,
+    //where the YouTube Data API lives, only worth changing to point at a mock server
+    pub api_base_url: String,
+    //the daily quota of the google cloud project, 10000 units unless google raised it
+    pub daily_quota: u32,
+    //calls costing more than one unit are blocked when they would leave less than this
+    pub quota_block_margin: u32,
+    //how long a cached response is used without asking youtube whether it changed
+    pub cache_ttl_secs: u64,
+    //the folder the responses are cached in
+    pub cache_dir: String,
+    //the file today's quota usage is kept in
+    pub quota_file: String,
+    //how often a call failing with a network error, a 5xx or a 429 is retried
+    pub retry_max_attempts: u32,
+    //the first wait before retrying, it doubles with every further attempt
+    pub retry_base_delay_ms: u64,
+    //no wait is ever longer than this, a longer Retry-After means we give up
+    pub retry_max_delay_ms: u64,
+    //how many results a search loads at once, more can be loaded with "load more"
+    pub search_page_size: u32,
+    //how many of the newest uploads of every subscribed channel go into the feed
+    pub feed_videos_per_channel: u32,
+    //the playlist w saves search results to, by id or title, it is created as a private playlist if none matches
+    pub save_for_later_playlist: String,
+    //whether autoplay radio starts out on, R turns it on or off while the tui runs
+    pub autoplay: bool,
+    //how many related tracks autoplay plays after a video before it stops
+    pub autoplay_tracks: usize,
+    //a google cloud api key, lets searching and browsing public videos work without signing in
+    pub api_key: String,
+}
+
+impl Config {
+    pub fn quota_limits(&self) -> QuotaLimits {
+        QuotaLimits {
+            daily_limit: self.daily_quota,
+            block_margin: self.quota_block_margin,
+        }
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.retry_max_attempts,
+            base_delay: Duration::from_millis(self.retry_base_delay_ms),
+            max_delay: Duration::from_millis(self.retry_max_delay_ms),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_pages: 20,
+            api_base_url: DEFAULT_BASE_URL.to_string(),
+            daily_quota: 10000,
+            quota_block_margin: 200,
+            cache_ttl_secs: 300,
+            cache_dir: CACHE_DIR.to_string(),
+            quota_file: QUOTA_FILE.to_string(),
+            retry_max_attempts: 3,
+            retry_base_delay_ms: 500,
+            retry_max_delay_ms: 30000,
+            search_page_size: 15,
+            feed_videos_per_channel: 5,
+            save_for_later_playlist: "Saved for later".to_string(),
+            autoplay: false,
+            autoplay_tracks: 10,
+            api_key: String::new(),
+        }
+    }
+}
+
+//reads config.json, a missing file just means the defaults are used
+pub fn load_config() -> Result<Config> {
+    if !Path::new(CONFIG_FILE).exists() {
+        return Ok(Config::default());
+    }
+    let json = fs::read_to_string(CONFIG_FILE).with_context(|| format!("Failed to read {}", CONFIG_FILE))?;
+    let config: Config = serde_json::from_str(&json).with_context(|| format!("{} is not valid", CONFIG_FILE))?;
+    Ok(config)
+}
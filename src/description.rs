@@ -0,0 +1,149 @@
+//description pulls the links and chapter timestamps out of a video description
+//the details pane lists them on their own lines, so links can be clicked and timestamps played from
+use std::fmt;
+
+//a point in the video the description points at, like "1:23 Chorus"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    pub secs: u64,
+    //the rest of the line, usually the name of the chapter
+    pub label: String,
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hours = self.secs / 3600;
+        let minutes = (self.secs % 3600) / 60;
+        let seconds = self.secs % 60;
+        if hours > 0 {
+            write!(f, "{}:{:02}:{:02}", hours, minutes, seconds)
+        } else {
+            write!(f, "{}:{:02}", minutes, seconds)
+        }
+    }
+}
+
+//every http and https link, in the order they appear, without repeats
+pub fn extract_links(text: &str) -> Vec<String> {
+    let mut links: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        let start = match word.find("https://").or_else(|| word.find("http://")) {
+            Some(start) => start,
+            None => continue,
+        };
+        let link = trim_link(&word[start..]);
+        if !links.iter().any(|known| known == link) {
+            links.push(link.to_string());
+        }
+    }
+    links
+}
+
+//punctuation around a link belongs to the sentence, not the link
+//a closing bracket is kept when the link opened it, like wikipedia's Name_(band) pages
+fn trim_link(link: &str) -> &str {
+    let mut link = link;
+    while let Some(last) = link.chars().last() {
+        let unbalanced = match last {
+            ')' => link.matches('(').count() < link.matches(')').count(),
+            ']' => link.matches('[').count() < link.matches(']').count(),
+            '.' | ',' | '!' | '?' | ';' | ':' | '"' | '\'' => true,
+            _ => false,
+        };
+        if !unbalanced {
+            break;
+        }
+        link = &link[..link.len() - 1];
+    }
+    link
+}
+
+//the first timestamp of every line that has one, the rest of the line is its label
+pub fn extract_timestamps(text: &str) -> Vec<Timestamp> {
+    text.lines()
+        .filter_map(|line| {
+            let (idx, secs) = line
+                .split_whitespace()
+                .enumerate()
+                .find_map(|(idx, word)| parse_timestamp(word).map(|secs| (idx, secs)))?;
+            let label = line
+                .split_whitespace()
+                .enumerate()
+                .filter(|(i, _)| *i != idx)
+                .map(|(_, word)| word)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let label = label.trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '–' | '|' | ':')).to_string();
+            Some(Timestamp { secs, label })
+        })
+        .collect()
+}
+
+//m:ss, mm:ss or h:mm:ss, optionally wrapped in brackets
+fn parse_timestamp(word: &str) -> Option<u64> {
+    let word = word.trim_matches(['(', ')', '[', ']', '-', '–', '|']);
+    let parts: Vec<&str> = word.split(':').collect();
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    //everything after the first part is two digits below 60
+    if parts[1..].iter().any(|part| part.len() != 2 || !part.parse::<u64>().is_ok_and(|value| value < 60)) {
+        return None;
+    }
+    let mut secs = 0;
+    for part in parts {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_in_brackets_and_with_hours() {
+        assert_eq!(parse_timestamp("[1:02:03]"), Some(3723));
+        assert_eq!(parse_timestamp("(4:05)"), Some(245));
+        assert_eq!(parse_timestamp("12:34"), Some(754));
+    }
+
+    #[test]
+    fn malformed_timestamps_are_ignored() {
+        assert_eq!(parse_timestamp("12:345"), None);
+        assert_eq!(parse_timestamp("1:60"), None);
+        assert_eq!(parse_timestamp("1:2"), None);
+        assert_eq!(parse_timestamp("1:02:03:04"), None);
+        assert_eq!(parse_timestamp("10:30am"), None);
+        assert!(extract_timestamps("Runtime 12:345 minutes").is_empty());
+    }
+
+    #[test]
+    fn labels_keep_inner_dashes() {
+        let timestamps = extract_timestamps("Tracklist:\n0:00 - Intro\n[1:02:03] Lo-fi Beats - Part 2\n4:05 | Rock-n-roll");
+        assert_eq!(
+            timestamps,
+            vec![
+                Timestamp { secs: 0, label: "Intro".to_string() },
+                Timestamp { secs: 3723, label: "Lo-fi Beats - Part 2".to_string() },
+                Timestamp { secs: 245, label: "Rock-n-roll".to_string() },
+            ]
+        );
+        assert_eq!(timestamps[1].to_string(), "1:02:03");
+        assert_eq!(timestamps[2].to_string(), "4:05");
+    }
+
+    #[test]
+    fn wikipedia_links_keep_their_closing_bracket() {
+        let links = extract_links(
+            "About the band: https://en.wikipedia.org/wiki/Queen_(band). (see https://example.com/tour) https://example.com/tour",
+        );
+        assert_eq!(links, ["https://en.wikipedia.org/wiki/Queen_(band)", "https://example.com/tour"]);
+    }
+
+    #[test]
+    fn links_lose_trailing_punctuation() {
+        let links = extract_links("Merch:https://shop.example.com, lyrics \"https://example.com/lyrics?id=1\"!");
+        assert_eq!(links, ["https://shop.example.com", "https://example.com/lyrics?id=1"]);
+    }
+}
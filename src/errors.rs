@@ -0,0 +1,130 @@
+//errors turns failed YouTube API calls into something the user can act on
+//google sends its errors as json, the reason inside tells us what went wrong
+use serde::Deserialize;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("YouTube API quota exceeded: {0}")]
+    QuotaExceeded(String),
+    #[error("Invalid credentials: {0}")]
+    InvalidCredentials(String),
+    #[error("Invalid API key: {0}")]
+    InvalidApiKey(String),
+    //there is no login, public says whether an api key would have been enough
+    #[error("Signing in is needed for {what}")]
+    SignInRequired { what: String, public: bool },
+    #[error("Forbidden ({reason}): {message}")]
+    Forbidden { reason: String, message: String },
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Local quota budget reached, {remaining} units left today")]
+    QuotaBudgetReached { remaining: u32 },
+    #[error("Rate limited: {message}")]
+    RateLimited { message: String, retry_after: Option<u64> },
+    #[error("YouTube server error ({status}): {message}")]
+    ServerError { status: u16, message: String, retry_after: Option<u64> },
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("YouTube API error ({status}): {message}")]
+    Other { status: u16, message: String },
+}
+
+//{"error": {"code": 403, "message": "...", "errors": [{"reason": "quotaExceeded", ...}]}}
+#[derive(Debug, Deserialize)]
+struct GoogleErrorResponse {
+    error: GoogleError,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleError {
+    message: String,
+    #[serde(default)]
+    errors: Vec<GoogleErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleErrorDetail {
+    reason: String,
+}
+
+impl ApiError {
+    //builds the error from the status and body of a failed response
+    //retry_after is the Retry-After header in seconds, if google sent one
+    pub fn from_response(status: u16, body: &str, retry_after: Option<u64>) -> Self {
+        let (reason, message) = match serde_json::from_str::<GoogleErrorResponse>(body) {
+            Ok(parsed) => {
+                let reason = parsed
+                    .error
+                    .errors
+                    .first()
+                    .map(|detail| detail.reason.clone())
+                    .unwrap_or_default();
+                (reason, parsed.error.message)
+            }
+            //not every error comes from the api itself, proxies can send html or plain text
+            Err(_) => (String::new(), body.trim().to_string()),
+        };
+
+        match (status, reason.as_str()) {
+            (_, "quotaExceeded") | (_, "dailyLimitExceeded") => ApiError::QuotaExceeded(message),
+            //google only says the key is wrong in the message of a 400
+            (_, "keyInvalid") => ApiError::InvalidApiKey(message),
+            (400, _) if message.contains("API key not valid") => ApiError::InvalidApiKey(message),
+            (429, _) | (_, "rateLimitExceeded") | (_, "userRateLimitExceeded") => {
+                ApiError::RateLimited { message, retry_after }
+            }
+            (401, _) | (_, "authError") => ApiError::InvalidCredentials(message),
+            (403, _) => ApiError::Forbidden { reason, message },
+            (404, _) => ApiError::NotFound(message),
+            (500..=599, _) => ApiError::ServerError { status, message, retry_after },
+            _ => ApiError::Other { status, message },
+        }
+    }
+
+    //what the user should do about it, shown in the status bar
+    pub fn guidance(&self) -> String {
+        match self {
+            ApiError::QuotaExceeded(_) => "The daily YouTube API quota is used up. It resets at midnight Pacific time, until then you can use credentials from another Google Cloud project.".to_string(),
+            ApiError::InvalidCredentials(_) => "Your login is no longer valid. Press 'a' to authenticate again, or delete token.json if that keeps failing.".to_string(),
+            ApiError::InvalidApiKey(_) => "The api_key in config.json isn't valid. Check it in the Google Cloud console and make sure the YouTube Data API v3 is enabled for its project.".to_string(),
+            ApiError::SignInRequired { public: true, .. } => "You aren't signed in. Press 'a' to sign in, or set api_key in config.json to search and browse without signing in.".to_string(),
+            ApiError::SignInRequired { what, .. } => format!("The {} request needs your account. Press 'a' to sign in.", what),
+            ApiError::Forbidden { reason, .. } if reason == "commentsDisabled" => "The uploader turned off comments for this video.".to_string(),
+            ApiError::Forbidden { reason, .. } => format!("YouTube refused the request ({}). Make sure the YouTube Data API v3 is enabled and your account is added as a test user.", reason),
+            ApiError::QuotaBudgetReached { remaining } => format!("Only {} units of today's API quota are left, so expensive calls like searching are paused until midnight Pacific time. You can change daily_quota and quota_block_margin in config.json.", remaining),
+            ApiError::NotFound(_) => "YouTube couldn't find what was requested, it may have been deleted or made private.".to_string(),
+            ApiError::RateLimited { .. } => "YouTube is limiting how fast requests can be sent, wait a little and try again.".to_string(),
+            ApiError::ServerError { status, .. } => format!("YouTube had a problem on its side ({}), try again in a few minutes.", status),
+            ApiError::Network(_) => "Couldn't reach YouTube, check your internet connection.".to_string(),
+            ApiError::Other { status, message } => format!("YouTube API error ({}): {}", status, message),
+        }
+    }
+
+    //errors that may go away by themselves, these are worth retrying
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ApiError::RateLimited { .. } | ApiError::ServerError { .. } | ApiError::Network(_)
+        )
+    }
+
+    //how long google asked us to wait before trying again
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ApiError::RateLimited { retry_after, .. } | ApiError::ServerError { retry_after, .. } => {
+                retry_after.map(Duration::from_secs)
+            }
+            _ => None,
+        }
+    }
+}
+
+//turns any error from an api call into a message for the status bar
+pub fn describe(err: &anyhow::Error) -> String {
+    match err.downcast_ref::<ApiError>() {
+        Some(api_error) => api_error.guidance(),
+        None => err.to_string(),
+    }
+}
@@ -0,0 +1,77 @@
+//history keeps a local record of everything that was played
+//every play is appended as one json object per line to history.jsonl
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+//where the play was started from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaySource {
+    Search,
+    Playlist,
+    History,
+    //the latest uploads feed of the subscriptions tab
+    Feed,
+    //a channel page
+    Channel,
+    //the details pane of a video, possibly from one of its timestamps
+    Details,
+    //picked by autoplay from the mix of the track before
+    Radio,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub video_id: String,
+    pub title: String,
+    pub uploader: String,
+    pub started_at: DateTime<Local>,
+    pub listened_secs: u64,
+    pub source: PlaySource,
+}
+
+//appends a single entry to the end of the history file, creating it if needed
+pub fn append_entry(entry: &HistoryEntry) -> Result<()> {
+    let line = serde_json::to_string(entry)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_FILE)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+//reads the whole history, newest entries first
+//lines that can't be parsed are skipped so one bad write doesn't lose everything
+pub fn load_history() -> Result<Vec<HistoryEntry>> {
+    if !Path::new(HISTORY_FILE).exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(HISTORY_FILE)?;
+    let mut entries: Vec<HistoryEntry> = data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+//case insensitive search on title and uploader, an empty query matches everything
+pub fn filter_history<'a>(entries: &'a [HistoryEntry], query: &str) -> Vec<&'a HistoryEntry> {
+    let query = query.to_lowercase();
+    entries
+        .iter()
+        .filter(|entry| {
+            query.is_empty()
+                || entry.title.to_lowercase().contains(&query)
+                || entry.uploader.to_lowercase().contains(&query)
+        })
+        .collect()
+}
@@ -0,0 +1,223 @@
+//local_playlists keeps playlists that only live on this machine, in local_playlists.json
+//they are edited without the api, so they work without logging in and cost no quota
+use crate::youtube::{Playlist, PlaylistEntry};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const LOCAL_PLAYLISTS_FILE: &str = "local_playlists.json";
+
+//ids of local playlists start with this, so they can't be mistaken for youtube ones
+const ID_PREFIX: &str = "local-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalPlaylist {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub videos: Vec<LocalVideo>,
+}
+
+//only the id is needed to play a video, the rest is shown in the list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalVideo {
+    pub video_id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub uploader: String,
+    #[serde(default)]
+    pub channel_id: String,
+}
+
+impl LocalPlaylist {
+    pub fn new(title: &str, description: &str) -> Self {
+        Self {
+            id: format!("{}{}", ID_PREFIX, Utc::now().timestamp_millis()),
+            title: title.to_string(),
+            description: description.to_string(),
+            videos: vec![],
+        }
+    }
+
+    //how the playlist is listed next to the youtube ones
+    pub fn to_playlist(&self) -> Playlist {
+        Playlist {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            privacy: "local".to_string(),
+            system: false,
+            local: true,
+        }
+    }
+
+    //the videos as playlist items, the item id is the position since there is no youtube item behind them
+    pub fn entries(&self) -> Vec<PlaylistEntry> {
+        self.videos
+            .iter()
+            .enumerate()
+            .map(|(position, video)| PlaylistEntry {
+                item_id: position.to_string(),
+                video_id: video.video_id.clone(),
+                //videos added by id alone have no title yet
+                title: if video.title.is_empty() { video.video_id.clone() } else { video.title.clone() },
+                uploader: video.uploader.clone(),
+                channel_id: video.channel_id.clone(),
+                position: position as u32,
+            })
+            .collect()
+    }
+
+    pub fn remove(&mut self, entry: &PlaylistEntry) -> Result<()> {
+        let idx = self.index_of(entry)?;
+        self.videos.remove(idx);
+        Ok(())
+    }
+
+    pub fn swap(&mut self, entry: &PlaylistEntry, other: &PlaylistEntry) -> Result<()> {
+        let (idx, other_idx) = (self.index_of(entry)?, self.index_of(other)?);
+        self.videos.swap(idx, other_idx);
+        Ok(())
+    }
+
+    //where the entry is in the videos, the file may have been edited since the entries were made
+    //so an error is returned when the position holds another video now
+    fn index_of(&self, entry: &PlaylistEntry) -> Result<usize> {
+        let idx = entry.position as usize;
+        match self.videos.get(idx) {
+            Some(video) if video.video_id == entry.video_id => Ok(idx),
+            _ => bail!("{} has changed since it was opened, open it again", self.title),
+        }
+    }
+}
+
+//every local playlist, a missing file means there are none yet
+pub fn load_local_playlists() -> Result<Vec<LocalPlaylist>> {
+    load_from(Path::new(LOCAL_PLAYLISTS_FILE))
+}
+
+pub fn save_local_playlists(playlists: &[LocalPlaylist]) -> Result<()> {
+    save_to(Path::new(LOCAL_PLAYLISTS_FILE), playlists)
+}
+
+//loads the playlists, lets change edit the one with the given id and saves them again
+//returns what change returned, or an error if there is no such playlist
+pub fn update<T>(playlist_id: &str, change: impl FnOnce(&mut LocalPlaylist) -> T) -> Result<T> {
+    update_in(Path::new(LOCAL_PLAYLISTS_FILE), playlist_id, change)
+}
+
+fn load_from(path: &Path) -> Result<Vec<LocalPlaylist>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).with_context(|| format!("{} is not valid", path.display()))
+}
+
+fn save_to(path: &Path, playlists: &[LocalPlaylist]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(playlists)?)?;
+    Ok(())
+}
+
+fn update_in<T>(path: &Path, playlist_id: &str, change: impl FnOnce(&mut LocalPlaylist) -> T) -> Result<T> {
+    let mut playlists = load_from(path)?;
+    let playlist = playlists
+        .iter_mut()
+        .find(|playlist| playlist.id == playlist_id)
+        .context("The local playlist no longer exists")?;
+    let result = change(playlist);
+    save_to(path, &playlists)?;
+    Ok(result)
+}
+
+pub fn delete(playlist_id: &str) -> Result<()> {
+    let mut playlists = load_local_playlists()?;
+    playlists.retain(|playlist| playlist.id != playlist_id);
+    save_local_playlists(&playlists)
+}
+
+pub fn find(playlist_id: &str) -> Result<LocalPlaylist> {
+    load_local_playlists()?
+        .into_iter()
+        .find(|playlist| playlist.id == playlist_id)
+        .context("The local playlist no longer exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(video_id: &str, title: &str) -> LocalVideo {
+        LocalVideo {
+            video_id: video_id.to_string(),
+            title: title.to_string(),
+            uploader: "uploader".to_string(),
+            channel_id: String::new(),
+        }
+    }
+
+    fn playlist() -> LocalPlaylist {
+        let mut playlist = LocalPlaylist::new("Mix", "");
+        playlist.videos = vec![video("aaaaaaaaaaa", "First"), video("bbbbbbbbbbb", ""), video("ccccccccccc", "Third")];
+        playlist
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("yt_tui_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn entries_are_numbered_by_position() {
+        let entries = playlist().entries();
+        let positions: Vec<_> = entries.iter().map(|entry| (entry.position, entry.item_id.as_str())).collect();
+        assert_eq!(positions, [(0, "0"), (1, "1"), (2, "2")]);
+        assert_eq!(entries[1].video_id, "bbbbbbbbbbb");
+        //videos without a title are shown by their id
+        assert_eq!(entries[1].title, "bbbbbbbbbbb");
+        assert_eq!(entries[2].title, "Third");
+    }
+
+    #[test]
+    fn remove_and_swap_check_the_video_id() {
+        let mut local = playlist();
+        let entries = local.entries();
+        local.swap(&entries[0], &entries[2]).unwrap();
+        let ids: Vec<_> = local.videos.iter().map(|video| video.video_id.as_str()).collect();
+        assert_eq!(ids, ["ccccccccccc", "bbbbbbbbbbb", "aaaaaaaaaaa"]);
+        //the old entries point at positions that hold other videos now
+        assert!(local.remove(&entries[0]).is_err());
+        assert!(local.swap(&entries[1], &entries[2]).is_err());
+        local.remove(&entries[1]).unwrap();
+        assert_eq!(local.videos.len(), 2);
+        //and past the end
+        assert!(local.remove(&entries[2]).is_err());
+    }
+
+    #[test]
+    fn update_on_a_missing_id_fails_and_keeps_the_file() {
+        let path = temp_path("local_playlists.json");
+        let local = playlist();
+        save_to(&path, std::slice::from_ref(&local)).unwrap();
+
+        let mut called = false;
+        assert!(update_in(&path, "local-missing", |_| called = true).is_err());
+        assert!(!called);
+
+        update_in(&path, &local.id, |playlist| playlist.title = "Renamed".to_string()).unwrap();
+        let saved = load_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].title, "Renamed");
+        assert_eq!(saved[0].videos.len(), 3);
+    }
+
+    #[test]
+    fn missing_file_means_no_playlists() {
+        assert!(load_from(&temp_path("no_such_file.json")).unwrap().is_empty());
+    }
+}
@@ -1,10 +1,24 @@
-use anyhow::Result;
 mod app_state;
 mod authenticate;
+mod cache;
 mod colors;
+mod comments;
+mod config;
+mod description;
+mod errors;
+mod history;
+mod local_playlists;
+mod playlist_files;
+mod playlist_form;
+mod quota;
 mod render;
+mod retry;
+mod search_filters;
+mod seen;
+mod stats;
 mod tui;
 mod utilities;
+mod youtube;
 
 
 #[tokio::main]
@@ -0,0 +1,439 @@
+//playlist_files writes playlists to m3u, json and csv files and reads them back
+//importing also takes plain lists of links, one per line, so playlists can come from other tools
+use anyhow::{bail, Context, Result};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    M3u,
+    Json,
+    Csv,
+    //one link, video id or title per line, only read
+    Text,
+}
+
+impl FileFormat {
+    //picked from the extension, files with any other extension are read as plain lists
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "m3u" | "m3u8" => FileFormat::M3u,
+            "json" => FileFormat::Json,
+            "csv" => FileFormat::Csv,
+            _ => FileFormat::Text,
+        }
+    }
+}
+
+//one video of a playlist file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileEntry {
+    #[serde(default)]
+    pub video_id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub uploader: String,
+    //in seconds, None for live streams and videos the file doesn't say it for
+    #[serde(default)]
+    pub duration: Option<u64>,
+    #[serde(default)]
+    pub url: String,
+}
+
+impl FileEntry {
+    //the youtube video the entry points at, None if it has to be searched for by its title
+    pub fn resolved_id(&self) -> Option<String> {
+        video_id_from(&self.video_id).or_else(|| video_id_from(&self.url))
+    }
+
+    //how the entry is named in the import report
+    pub fn describe(&self) -> String {
+        match (self.title.is_empty(), self.uploader.is_empty()) {
+            (false, false) => format!("{} - {}", self.uploader, self.title),
+            (false, true) => self.title.clone(),
+            _ if !self.url.is_empty() => self.url.clone(),
+            _ => self.video_id.clone(),
+        }
+    }
+}
+
+//the json layout, the title lets an import name the new playlist after the exported one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaylistFile {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub videos: Vec<FileEntry>,
+}
+
+pub fn watch_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", video_id)
+}
+
+//the id of a video from a bare id or any of the usual youtube links
+pub fn video_id_from(text: &str) -> Option<String> {
+    let text = text.trim();
+    if is_video_id(text) {
+        return Some(text.to_string());
+    }
+    let url = Url::parse(text).ok()?;
+    let host = url.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+    let id = match host {
+        "youtu.be" => url.path_segments()?.next().map(str::to_string),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+            let mut segments = url.path_segments()?;
+            match segments.next() {
+                Some("watch") => url.query_pairs().find(|(key, _)| key == "v").map(|(_, value)| value.into_owned()),
+                Some("shorts" | "embed" | "live" | "v") => segments.next().map(str::to_string),
+                _ => None,
+            }
+        }
+        _ => None,
+    }?;
+    is_video_id(&id).then_some(id)
+}
+
+fn is_video_id(text: &str) -> bool {
+    text.len() == 11 && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+//seconds of an iso 8601 duration like PT1H2M3S, None for live streams which have P0D
+pub fn duration_secs(duration: &str) -> Option<u64> {
+    let mut secs = 0;
+    let mut num = String::new();
+    for c in duration.chars() {
+        if c.is_ascii_digit() {
+            num.push(c);
+            continue;
+        }
+        let unit = match c {
+            'D' => 86400,
+            'H' => 3600,
+            'M' => 60,
+            'S' => 1,
+            _ => continue,
+        };
+        secs += num.parse::<u64>().unwrap_or(0) * unit;
+        num.clear();
+    }
+    (secs > 0).then_some(secs)
+}
+
+//writes the playlist in the format of the extension of path
+pub fn export(path: &Path, file: &PlaylistFile) -> Result<()> {
+    let text = match FileFormat::from_path(path) {
+        FileFormat::M3u => to_m3u(file),
+        FileFormat::Json => serde_json::to_string_pretty(file)?,
+        FileFormat::Csv => to_csv(file),
+        FileFormat::Text => bail!("Export to a .m3u, .json or .csv file"),
+    };
+    fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+//reads a playlist file, entries that name no video are kept so they can be searched for or reported
+pub fn import(path: &Path) -> Result<PlaylistFile> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut file = match FileFormat::from_path(path) {
+        FileFormat::M3u => from_m3u(&text),
+        //a bare list of videos is taken too
+        FileFormat::Json => serde_json::from_str::<PlaylistFile>(&text)
+            .or_else(|_| serde_json::from_str::<Vec<FileEntry>>(&text).map(|videos| PlaylistFile { title: None, videos }))
+            .with_context(|| format!("{} isn't a playlist export", path.display()))?,
+        FileFormat::Csv => from_csv(&text),
+        FileFormat::Text => from_text(&text),
+    };
+    file.videos.retain(|entry| !entry.describe().is_empty());
+    Ok(file)
+}
+
+fn to_m3u(file: &PlaylistFile) -> String {
+    let mut text = String::from("#EXTM3U\n");
+    if let Some(title) = &file.title {
+        text.push_str(&format!("#PLAYLIST:{}\n", title));
+    }
+    for entry in &file.videos {
+        //-1 is the m3u way of saying the length isn't known
+        let duration = entry.duration.map(|secs| secs as i64).unwrap_or(-1);
+        text.push_str(&format!("#EXTINF:{},{}\n{}\n", duration, entry.describe(), watch_url(&entry.video_id)));
+    }
+    text
+}
+
+fn from_m3u(text: &str) -> PlaylistFile {
+    let mut file = PlaylistFile::default();
+    //the #EXTINF line describes the location on the line after it
+    let mut pending: Option<(Option<u64>, String)> = None;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(title) = line.strip_prefix("#PLAYLIST:") {
+            file.title = Some(title.trim().to_string());
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+            pending = Some((duration.trim().parse::<u64>().ok(), title.trim().to_string()));
+        } else if !line.starts_with('#') {
+            let (duration, title) = pending.take().unwrap_or_default();
+            //local files are searched for by their name when the #EXTINF line has none
+            let title = if title.is_empty() && video_id_from(line).is_none() {
+                Path::new(line).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+            } else {
+                title
+            };
+            file.videos.push(FileEntry { title, duration, url: line.to_string(), ..FileEntry::default() });
+        }
+    }
+    file
+}
+
+const CSV_HEADER: &[&str] = &["video_id", "title", "uploader", "duration", "url"];
+
+fn to_csv(file: &PlaylistFile) -> String {
+    let mut text = CSV_HEADER.join(",") + "\n";
+    for entry in &file.videos {
+        let duration = entry.duration.map(|secs| secs.to_string()).unwrap_or_default();
+        let row = [
+            entry.video_id.as_str(),
+            entry.title.as_str(),
+            entry.uploader.as_str(),
+            duration.as_str(),
+            &watch_url(&entry.video_id),
+        ];
+        text.push_str(&row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+        text.push('\n');
+    }
+    text
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+//files from other tools name their columns differently, without a known header
+//the first column is taken as the link and the second as the title
+fn from_csv(text: &str) -> PlaylistFile {
+    let mut rows = csv_rows(text).into_iter();
+    let first = rows.next().unwrap_or_default();
+    let column = |names: &[&str]| {
+        first
+            .iter()
+            .position(|name| names.contains(&name.trim().to_lowercase().replace(' ', "_").as_str()))
+    };
+    let url_column = column(&["url", "link", "video_url"]);
+    let id_column = column(&["video_id", "id"]);
+    let title_column = column(&["title", "name", "track", "song"]);
+    let uploader_column = column(&["uploader", "channel", "artist", "author"]);
+    let duration_column = column(&["duration", "length", "seconds"]);
+
+    let has_header = url_column.is_some() || id_column.is_some() || title_column.is_some();
+    let (url_column, title_column) = if has_header { (url_column, title_column) } else { (Some(0), Some(1)) };
+    let rows: Vec<Vec<String>> = if has_header { rows.collect() } else { std::iter::once(first.clone()).chain(rows).collect() };
+
+    let field = |row: &[String], column: Option<usize>| {
+        column.and_then(|idx| row.get(idx)).map(|value| value.trim().to_string()).unwrap_or_default()
+    };
+    let videos = rows
+        .iter()
+        .filter(|row| row.iter().any(|value| !value.trim().is_empty()))
+        .map(|row| FileEntry {
+            video_id: field(row, id_column),
+            title: field(row, title_column),
+            uploader: field(row, uploader_column),
+            duration: field(row, duration_column).parse().ok(),
+            url: field(row, url_column),
+        })
+        .collect();
+    PlaylistFile { title: None, videos }
+}
+
+//splits csv text into rows of fields, quoted fields may hold commas, quotes and line breaks
+fn csv_rows(text: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            '\r' if !quoted => {}
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+//lines that aren't links or ids are searched for as titles, # starts a comment
+fn from_text(text: &str) -> PlaylistFile {
+    let videos = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match video_id_from(line) {
+            Some(_) => FileEntry { url: line.to_string(), ..FileEntry::default() },
+            None => FileEntry { title: line.to_string(), ..FileEntry::default() },
+        })
+        .collect();
+    PlaylistFile { title: None, videos }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(video_id: &str, title: &str, uploader: &str, duration: Option<u64>) -> FileEntry {
+        FileEntry {
+            video_id: video_id.to_string(),
+            title: title.to_string(),
+            uploader: uploader.to_string(),
+            duration,
+            url: watch_url(video_id),
+        }
+    }
+
+    fn sample() -> PlaylistFile {
+        PlaylistFile {
+            title: Some("Road trip".to_string()),
+            videos: vec![
+                entry("dQw4w9WgXcQ", "Never Gonna Give You Up", "Rick Astley", Some(213)),
+                entry("9bZkp7q19f0", "Gangnam Style, \"Official\"", "PSY", None),
+            ],
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("yt_tui_{}_{}", std::process::id(), name))
+    }
+
+    fn round_trip(name: &str) -> PlaylistFile {
+        let path = temp_path(name);
+        export(&path, &sample()).unwrap();
+        let file = import(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        file
+    }
+
+    #[test]
+    fn json_round_trip_keeps_everything() {
+        let file = round_trip("round_trip.json");
+        assert_eq!(file.title.as_deref(), Some("Road trip"));
+        assert_eq!(file.videos.len(), 2);
+        assert_eq!(file.videos[0].video_id, "dQw4w9WgXcQ");
+        assert_eq!(file.videos[0].duration, Some(213));
+        assert_eq!(file.videos[1].title, "Gangnam Style, \"Official\"");
+        assert_eq!(file.videos[1].duration, None);
+    }
+
+    #[test]
+    fn csv_round_trip_unquotes_fields() {
+        let file = round_trip("round_trip.csv");
+        assert_eq!(file.videos.len(), 2);
+        assert_eq!(file.videos[0].resolved_id().as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(file.videos[0].uploader, "Rick Astley");
+        assert_eq!(file.videos[0].duration, Some(213));
+        assert_eq!(file.videos[1].title, "Gangnam Style, \"Official\"");
+        assert_eq!(file.videos[1].duration, None);
+    }
+
+    #[test]
+    fn m3u_round_trip_keeps_ids_and_durations() {
+        let file = round_trip("round_trip.m3u");
+        assert_eq!(file.title.as_deref(), Some("Road trip"));
+        let ids: Vec<_> = file.videos.iter().map(|entry| entry.resolved_id().unwrap()).collect();
+        assert_eq!(ids, ["dQw4w9WgXcQ", "9bZkp7q19f0"]);
+        assert_eq!(file.videos[0].title, "Rick Astley - Never Gonna Give You Up");
+        assert_eq!(file.videos[0].duration, Some(213));
+        //-1 is written for an unknown length and read back as none
+        assert_eq!(file.videos[1].duration, None);
+    }
+
+    #[test]
+    fn csv_doubled_quotes_and_crlf() {
+        let file = from_csv("title,url\r\n\"Say \"\"hi\"\", now\",https://youtu.be/dQw4w9WgXcQ\r\nplain,9bZkp7q19f0\r\n");
+        assert_eq!(file.videos.len(), 2);
+        assert_eq!(file.videos[0].title, "Say \"hi\", now");
+        assert_eq!(file.videos[0].resolved_id().as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(file.videos[1].title, "plain");
+        assert_eq!(file.videos[1].url, "9bZkp7q19f0");
+    }
+
+    #[test]
+    fn csv_quoted_field_keeps_line_break() {
+        let rows = csv_rows("\"two\r\nlines\",x\r\n");
+        assert_eq!(rows, vec![vec!["two\r\nlines".to_string(), "x".to_string()]]);
+    }
+
+    #[test]
+    fn headerless_csv_reads_link_then_title() {
+        let file = from_csv("https://www.youtube.com/watch?v=dQw4w9WgXcQ,Never Gonna Give You Up\n,\nhttps://youtu.be/9bZkp7q19f0,Gangnam Style");
+        assert_eq!(file.videos.len(), 2);
+        assert_eq!(file.videos[0].resolved_id().as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(file.videos[0].title, "Never Gonna Give You Up");
+        assert_eq!(file.videos[1].resolved_id().as_deref(), Some("9bZkp7q19f0"));
+    }
+
+    #[test]
+    fn video_ids_from_links() {
+        let id = Some("dQw4w9WgXcQ".to_string());
+        assert_eq!(video_id_from("dQw4w9WgXcQ"), id);
+        assert_eq!(video_id_from("https://youtu.be/dQw4w9WgXcQ?t=42"), id);
+        assert_eq!(video_id_from("https://www.youtube.com/shorts/dQw4w9WgXcQ"), id);
+        assert_eq!(video_id_from("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ"), id);
+        assert_eq!(video_id_from("https://m.youtube.com/watch?list=PL1&v=dQw4w9WgXcQ"), id);
+        assert_eq!(video_id_from("https://music.youtube.com/watch?v=dQw4w9WgXcQ"), id);
+        assert_eq!(video_id_from("https://example.com/watch?v=dQw4w9WgXcQ"), None);
+        assert_eq!(video_id_from("https://www.youtube.com/playlist?list=PL1"), None);
+        assert_eq!(video_id_from("not a link"), None);
+    }
+
+    #[test]
+    fn extinf_without_comma() {
+        let file = from_m3u("#EXTM3U\n#EXTINF:180\nhttps://youtu.be/dQw4w9WgXcQ\n#EXTINF:-1\n/music/Some Song.mp3\n");
+        assert_eq!(file.videos.len(), 2);
+        assert_eq!(file.videos[0].duration, Some(180));
+        assert_eq!(file.videos[0].title, "");
+        assert_eq!(file.videos[0].resolved_id().as_deref(), Some("dQw4w9WgXcQ"));
+        //local files are named after the file so they can be searched for
+        assert_eq!(file.videos[1].duration, None);
+        assert_eq!(file.videos[1].title, "Some Song");
+    }
+
+    #[test]
+    fn iso_durations() {
+        assert_eq!(duration_secs("PT3M33S"), Some(213));
+        assert_eq!(duration_secs("PT1H2M3S"), Some(3723));
+        assert_eq!(duration_secs("P1DT1S"), Some(86401));
+        //live streams have no length
+        assert_eq!(duration_secs("P0D"), None);
+    }
+
+    #[test]
+    fn text_lists_take_links_and_titles() {
+        let file = from_text("# my list\nhttps://youtu.be/dQw4w9WgXcQ\n\nSome Song\r\n");
+        assert_eq!(file.videos.len(), 2);
+        assert_eq!(file.videos[0].resolved_id().as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(file.videos[1].title, "Some Song");
+        assert_eq!(file.videos[1].resolved_id(), None);
+    }
+}
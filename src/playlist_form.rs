@@ -0,0 +1,288 @@
+//playlist_form walks the user through creating, opening, renaming, changing the privacy of, deleting,
+//exporting and importing a playlist
+//one value is asked for at a time, the event handler sends the request once the last step is done
+use crate::local_playlists::LocalVideo;
+use crate::youtube::Playlist;
+
+pub const PRIVACY_OPTIONS: &[&str] = &["private", "unlisted", "public"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistAction {
+    Create,
+    //a playlist kept in local_playlists.json, it has no privacy
+    CreateLocal,
+    //shows the videos of the playlist so they can be removed or moved
+    Open,
+    Rename,
+    ChangePrivacy,
+    Delete,
+    //writes the videos to an m3u, json or csv file
+    Export,
+    //adds the videos of a file to a new or existing playlist
+    Import,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormStep {
+    //the number of the playlist in the list
+    Number,
+    Title,
+    Description,
+    Privacy,
+    //y or n, only asked before deleting
+    Confirm,
+    //the file exported to or imported from
+    Path,
+    //the number of the playlist an import goes into, empty for a new one
+    Target,
+}
+
+impl PlaylistAction {
+    fn steps(self) -> &'static [FormStep] {
+        match self {
+            PlaylistAction::Create => &[FormStep::Title, FormStep::Description, FormStep::Privacy],
+            PlaylistAction::CreateLocal => &[FormStep::Title, FormStep::Description],
+            PlaylistAction::Open => &[FormStep::Number],
+            PlaylistAction::Rename => &[FormStep::Number, FormStep::Title],
+            PlaylistAction::ChangePrivacy => &[FormStep::Number, FormStep::Privacy],
+            PlaylistAction::Delete => &[FormStep::Number, FormStep::Confirm],
+            PlaylistAction::Export => &[FormStep::Number, FormStep::Path],
+            PlaylistAction::Import => &[FormStep::Path, FormStep::Target],
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PlaylistAction::Create => "New playlist",
+            PlaylistAction::CreateLocal => "New local playlist",
+            PlaylistAction::Open => "Open playlist",
+            PlaylistAction::Rename => "Rename playlist",
+            PlaylistAction::ChangePrivacy => "Change privacy",
+            PlaylistAction::Delete => "Delete playlist",
+            PlaylistAction::Export => "Export playlist",
+            PlaylistAction::Import => "Import playlist",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaylistForm {
+    pub action: PlaylistAction,
+    pub step: FormStep,
+    //what the user is typing in the current step
+    pub input: String,
+    //the playlist picked in the number step, None while creating one
+    pub target: Option<Playlist>,
+    pub title: String,
+    pub description: String,
+    //index into PRIVACY_OPTIONS
+    pub privacy: usize,
+    pub path: String,
+}
+
+impl PlaylistForm {
+    pub fn new(action: PlaylistAction) -> Self {
+        Self {
+            action,
+            step: action.steps()[0],
+            input: String::new(),
+            target: None,
+            title: String::new(),
+            description: String::new(),
+            privacy: 0,
+            path: String::new(),
+        }
+    }
+
+    //the question shown for the current step
+    pub fn prompt(&self) -> String {
+        match self.step {
+            FormStep::Number => "Number of the playlist: ".to_string(),
+            FormStep::Title => "Title: ".to_string(),
+            FormStep::Description => "Description (optional): ".to_string(),
+            FormStep::Privacy => "Privacy (Up/Down to change, Enter to save): ".to_string(),
+            FormStep::Confirm => format!(
+                "Delete '{}'? This can't be undone (y/n) ",
+                self.target.as_ref().map(|playlist| playlist.title.as_str()).unwrap_or_default()
+            ),
+            FormStep::Path if self.action == PlaylistAction::Export => "Export to (.m3u, .json or .csv): ".to_string(),
+            FormStep::Path => "Import from (.m3u, .json, .csv or a list of links): ".to_string(),
+            FormStep::Target => "Number of the playlist to add to, empty for a new one: ".to_string(),
+        }
+    }
+
+    //steps that take typed text, the others only react to a few keys
+    pub fn is_typing(&self) -> bool {
+        matches!(
+            self.step,
+            FormStep::Number | FormStep::Title | FormStep::Description | FormStep::Path | FormStep::Target
+        )
+    }
+
+    //steps that only take digits
+    pub fn is_numeric(&self) -> bool {
+        matches!(self.step, FormStep::Number | FormStep::Target)
+    }
+
+    pub fn privacy(&self) -> &'static str {
+        PRIVACY_OPTIONS[self.privacy]
+    }
+
+    pub fn cycle_privacy(&mut self, forward: bool) {
+        let len = PRIVACY_OPTIONS.len();
+        self.privacy = if forward { (self.privacy + 1) % len } else { (self.privacy + len - 1) % len };
+    }
+
+    //takes the input of the current step and moves on to the next one
+    //returns Ok(true) once every step is done, Err with a message if the input can't be used
+    pub fn submit(&mut self, playlists: &[Playlist]) -> Result<bool, String> {
+        match self.step {
+            FormStep::Number => {
+                let playlist = self
+                    .input
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|idx| *idx > 0)
+                    .and_then(|idx| playlists.get(idx - 1))
+                    .ok_or_else(|| "Playlist number out of range.".to_string())?;
+                if playlist.system && !matches!(self.action, PlaylistAction::Open | PlaylistAction::Export) {
+                    return Err(format!("{} is managed by YouTube and can't be changed.", playlist.title));
+                }
+                if playlist.local && self.action == PlaylistAction::ChangePrivacy {
+                    return Err(format!("{} is a local playlist, only you can see it.", playlist.title));
+                }
+                //the current values are the starting point of the edit
+                self.title = playlist.title.clone();
+                self.description = playlist.description.clone();
+                self.privacy = PRIVACY_OPTIONS
+                    .iter()
+                    .position(|privacy| *privacy == playlist.privacy)
+                    .unwrap_or(0);
+                self.target = Some(playlist.clone());
+            }
+            FormStep::Title => {
+                if self.input.trim().is_empty() {
+                    return Err("The title can't be empty.".to_string());
+                }
+                self.title = self.input.trim().to_string();
+            }
+            FormStep::Description => self.description = self.input.trim().to_string(),
+            FormStep::Path => {
+                if self.input.trim().is_empty() {
+                    return Err("The file name can't be empty.".to_string());
+                }
+                self.path = self.input.trim().to_string();
+            }
+            //the playlist is created once the file has been read
+            FormStep::Target if self.input.is_empty() => self.target = None,
+            FormStep::Target => {
+                let playlist = self
+                    .input
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|idx| *idx > 0)
+                    .and_then(|idx| playlists.get(idx - 1))
+                    .ok_or_else(|| "Playlist number out of range.".to_string())?;
+                if playlist.system {
+                    return Err(format!("{} is managed by YouTube and can't be changed.", playlist.title));
+                }
+                self.target = Some(playlist.clone());
+            }
+            FormStep::Privacy | FormStep::Confirm => {}
+        }
+
+        let steps = self.action.steps();
+        let next = steps.iter().position(|step| *step == self.step).map(|idx| idx + 1);
+        match next.and_then(|idx| steps.get(idx)) {
+            Some(step) => {
+                self.step = *step;
+                //renaming starts from the old title and exporting from a file named after the playlist
+                self.input = match step {
+                    FormStep::Title => self.title.clone(),
+                    FormStep::Path if self.action == PlaylistAction::Export => format!("{}.m3u", file_name(&self.title)),
+                    _ => String::new(),
+                };
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+}
+
+//the title without the characters file systems don't allow
+fn file_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    match name.trim() {
+        "" => "playlist".to_string(),
+        name => name.to_string(),
+    }
+}
+
+//a video waiting for the user to pick the playlist it is added to
+#[derive(Debug, Clone)]
+pub struct PendingAdd {
+    //a local playlist keeps the title and uploader too
+    pub video: LocalVideo,
+    //the number of the playlist being typed
+    pub input: String,
+}
+
+impl PendingAdd {
+    //the playlist the typed number points to, the ones youtube manages can't be added to
+    pub fn target<'a>(&self, playlists: &'a [Playlist]) -> Result<&'a Playlist, String> {
+        let playlist = self
+            .input
+            .parse::<usize>()
+            .ok()
+            .filter(|idx| *idx > 0)
+            .and_then(|idx| playlists.get(idx - 1))
+            .ok_or_else(|| "Playlist number out of range.".to_string())?;
+        if playlist.system {
+            return Err(format!("{} is managed by YouTube and can't be changed.", playlist.title));
+        }
+        Ok(playlist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist(title: &str, system: bool) -> Playlist {
+        Playlist {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            description: String::new(),
+            privacy: "private".to_string(),
+            system,
+            local: false,
+        }
+    }
+
+    fn pending(input: &str) -> PendingAdd {
+        PendingAdd {
+            video: LocalVideo {
+                video_id: "aaaaaaaaaaa".to_string(),
+                title: "Song".to_string(),
+                uploader: String::new(),
+                channel_id: String::new(),
+            },
+            input: input.to_string(),
+        }
+    }
+
+    #[test]
+    fn adding_to_a_system_playlist_is_rejected() {
+        let playlists = vec![playlist("Liked videos", true), playlist("Mine", false)];
+        assert_eq!(
+            pending("1").target(&playlists).unwrap_err(),
+            "Liked videos is managed by YouTube and can't be changed."
+        );
+        assert_eq!(pending("2").target(&playlists).unwrap().title, "Mine");
+        assert_eq!(pending("3").target(&playlists).unwrap_err(), "Playlist number out of range.");
+        assert_eq!(pending("0").target(&playlists).unwrap_err(), "Playlist number out of range.");
+    }
+}
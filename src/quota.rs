@@ -0,0 +1,173 @@
+//quota keeps track of how many YouTube Data API units were spent today
+//google resets the daily quota at midnight Pacific time, so the day is counted in Pacific time too
+//the totals are kept in quota.json so restarting the tui doesn't forget them
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const QUOTA_FILE: &str = "quota.json";
+
+//estimated unit cost of the calls we make, from the YouTube Data API quota table
+pub const COST_READ: u32 = 1;
+pub const COST_SEARCH: u32 = 100;
+//inserts, updates and deletes
+pub const COST_WRITE: u32 = 50;
+
+//the share of the daily limit below which the status bar warns about the quota
+const WARN_PERCENT: u32 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QuotaUsage {
+    day: NaiveDate,
+    used: u32,
+}
+
+//how much quota we are allowed to use, both come from config.json
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaLimits {
+    pub daily_limit: u32,
+    //expensive calls are blocked when they would leave less than this many units
+    pub block_margin: u32,
+}
+
+impl QuotaLimits {
+    //true if the remaining budget is low enough to warn about
+    pub fn is_low(&self, remaining: u32) -> bool {
+        //widened so large limits from config.json can't overflow
+        u64::from(remaining) * 100 < u64::from(self.daily_limit) * u64::from(WARN_PERCENT)
+    }
+}
+
+//the Pacific date at the given moment, daylight saving time follows the US rules
+//(from the second sunday of march to the first sunday of november, switching at 2am local time)
+pub fn pacific_today(now: DateTime<Utc>) -> NaiveDate {
+    let year = now.year();
+    //2am PST is 10:00 UTC, 2am PDT is 09:00 UTC
+    let dst_start = Utc.from_utc_datetime(&nth_sunday(year, 3, 2).and_hms_opt(10, 0, 0).unwrap());
+    let dst_end = Utc.from_utc_datetime(&nth_sunday(year, 11, 1).and_hms_opt(9, 0, 0).unwrap());
+    let offset = if now >= dst_start && now < dst_end { 7 } else { 8 };
+    (now - Duration::hours(offset)).date_naive()
+}
+
+fn nth_sunday(year: i32, month: u32, n: u32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n as u8).unwrap()
+}
+
+fn load_usage(path: &Path) -> Result<QuotaUsage> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn save_usage(path: &Path, usage: &QuotaUsage) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(usage)?)?;
+    Ok(())
+}
+
+//starting a new day once midnight Pacific has passed
+fn roll_over(usage: &mut QuotaUsage) {
+    let today = pacific_today(Utc::now());
+    if usage.day != today {
+        usage.day = today;
+        usage.used = 0;
+    }
+}
+
+//today's usage and the file it is kept in, the youtube client shares one between all its copies
+pub struct QuotaTracker {
+    path: PathBuf,
+    usage: Mutex<QuotaUsage>,
+}
+
+impl QuotaTracker {
+    //picks up the total an earlier run wrote to the file, a missing file starts at zero
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let usage = load_usage(&path).unwrap_or(QuotaUsage { day: pacific_today(Utc::now()), used: 0 });
+        Self { path, usage: Mutex::new(usage) }
+    }
+
+    //units used since the last Pacific midnight
+    pub fn used_today(&self) -> u32 {
+        let mut usage = self.usage.lock().unwrap();
+        roll_over(&mut usage);
+        usage.used
+    }
+
+    pub fn remaining(&self, limits: QuotaLimits) -> u32 {
+        limits.daily_limit.saturating_sub(self.used_today())
+    }
+
+    //checks whether a call with this cost may be made, returns the remaining budget if not
+    //reads that cost a single unit are always let through, the tui is useless without them
+    pub fn check(&self, cost: u32, limits: QuotaLimits) -> std::result::Result<(), u32> {
+        let remaining = self.remaining(limits);
+        if cost > COST_READ && remaining < cost.saturating_add(limits.block_margin) {
+            return Err(remaining);
+        }
+        Ok(())
+    }
+
+    //adds the cost of a call that was sent to today's total and writes it to the file
+    pub fn record(&self, cost: u32) {
+        let mut usage = self.usage.lock().unwrap();
+        roll_over(&mut usage);
+        usage.used = usage.used.saturating_add(cost);
+        if let Err(e) = save_usage(&self.path, &usage) {
+            eprintln!("Failed to write quota usage: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pacific_day(utc: &str) -> String {
+        let now = DateTime::parse_from_rfc3339(utc).unwrap().with_timezone(&Utc);
+        pacific_today(now).to_string()
+    }
+
+    #[test]
+    fn midnight_before_and_after_march_switch() {
+        //dst starts on 2026-03-08, midnight is 08:00 UTC before and 07:00 UTC after
+        assert_eq!(pacific_day("2026-03-08T07:59:59Z"), "2026-03-07");
+        assert_eq!(pacific_day("2026-03-08T08:00:00Z"), "2026-03-08");
+        assert_eq!(pacific_day("2026-03-08T09:59:59Z"), "2026-03-08");
+        assert_eq!(pacific_day("2026-03-08T10:00:00Z"), "2026-03-08");
+        assert_eq!(pacific_day("2026-03-09T06:59:59Z"), "2026-03-08");
+        assert_eq!(pacific_day("2026-03-09T07:00:00Z"), "2026-03-09");
+    }
+
+    #[test]
+    fn midnight_before_and_after_november_switch() {
+        //dst ends on 2026-11-01, midnight is 07:00 UTC before and 08:00 UTC after
+        assert_eq!(pacific_day("2026-11-01T06:59:59Z"), "2026-10-31");
+        assert_eq!(pacific_day("2026-11-01T07:00:00Z"), "2026-11-01");
+        assert_eq!(pacific_day("2026-11-01T08:59:59Z"), "2026-11-01");
+        assert_eq!(pacific_day("2026-11-01T09:00:00Z"), "2026-11-01");
+        assert_eq!(pacific_day("2026-11-02T07:59:59Z"), "2026-11-01");
+        assert_eq!(pacific_day("2026-11-02T08:00:00Z"), "2026-11-02");
+    }
+
+    #[test]
+    fn switch_dates_follow_the_year() {
+        //2027 switches on 2027-03-14 and 2027-11-07
+        assert_eq!(pacific_day("2027-03-14T07:30:00Z"), "2027-03-13");
+        assert_eq!(pacific_day("2027-03-15T07:30:00Z"), "2027-03-15");
+        assert_eq!(pacific_day("2027-11-07T07:30:00Z"), "2027-11-07");
+        assert_eq!(pacific_day("2027-11-08T07:30:00Z"), "2027-11-07");
+    }
+
+    #[test]
+    fn is_low_with_large_limits() {
+        let limits = QuotaLimits { daily_limit: u32::MAX, block_margin: 0 };
+        assert!(limits.is_low(0));
+        assert!(!limits.is_low(u32::MAX));
+        let limits = QuotaLimits { daily_limit: 10_000, block_margin: 500 };
+        assert!(limits.is_low(999));
+        assert!(!limits.is_low(1_000));
+    }
+}
@@ -1,8 +1,19 @@
 // render contains functions that create widgets that the main tui class will use 
 //think of it as a widget factory
 //all render_xy functions return a paragraph and some have parameteres that they will get from the app state
+use crate::app_state::{self, SelectionAction};
 use crate::colors::Theme;
-use tui::{layout::Alignment, style::{Color, Modifier, Style},text::{Span, Spans}, widgets::{Block, BorderType, Borders, Paragraph, Wrap}, };
+use crate::history::{self, HistoryEntry, PlaySource};
+use crate::playlist_form::{FormStep, PendingAdd, PlaylistForm, PRIVACY_OPTIONS};
+use crate::quota::QuotaLimits;
+use crate::search_filters::{SearchFilters, FILTER_FIELDS};
+use crate::stats::{ListeningStats, StatsGrouping, StatsRange};
+use crate::comments::CommentsView;
+use crate::description;
+use crate::youtube::Comment;
+use crate::youtube::{Access, Channel, FeedVideo, Playlist, PlaylistEntry, Rating, SearchResult, Subscription, VideoDetails};
+use std::collections::{HashMap, HashSet};
+use tui::{layout::Alignment, style::{Color, Modifier, Style},text::{Span, Spans}, widgets::{BarChart, Block, BorderType, Borders, Paragraph, Sparkline, Wrap}, };
 
 pub fn render_home<'a>(theme: &Theme, themes: &'a [String], theme_selection_mode: bool, theme_number_input: &'a str, ) -> Paragraph<'a> 
 {
@@ -70,51 +81,112 @@ pub fn render_home<'a>(theme: &Theme, themes: &'a [String], theme_selection_mode
         )
 }
 
-pub fn render_playlists<'a>( theme: &Theme, playlists: &'a [(String, String)], playlist_selection_mode: bool, playlist_number_input: &'a str,) -> Paragraph<'a> 
+//cursor is the highlighted playlist, visible_rows the inner height of the box
+//only the list scrolls, the dialog and the number prompt stay below it
+#[allow(clippy::too_many_arguments)]
+pub fn render_playlists<'a>( theme: &Theme, playlists: &'a [Playlist], playlists_total: u32, playlists_loading: bool, selection: Option<&'a str>, form: Option<&PlaylistForm>, save_for_later: &str, cursor: usize, visible_rows: u16,) -> Paragraph<'a> 
 {
-    let mut lines: Vec<Spans> = if playlists.is_empty() 
+    let mut footer: Vec<Spans> = vec![];
+    //the open playlist dialog, one question at a time
+    if let Some(form) = form 
+    {
+        footer.push(Spans::from(vec![Span::raw("")]));
+        footer.push(Spans::from(vec![Span::styled(
+            form.action.label(),
+            Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD),
+        )]));
+        let answer = if form.step == FormStep::Privacy 
+        {
+            //all options with the chosen one highlighted
+            PRIVACY_OPTIONS
+                .iter()
+                .map(|privacy| if *privacy == form.privacy() { format!("[{}]", privacy) } else { format!(" {} ", privacy) })
+                .collect::<Vec<_>>()
+                .join(" ")
+        } 
+        else 
+        {
+            form.input.clone()
+        };
+        footer.push(Spans::from(vec![
+            Span::raw(form.prompt()),
+            Span::styled(answer, Style::default().fg(theme.playlist_number.0).add_modifier(Modifier::BOLD)),
+        ]));
+        footer.push(Spans::from(vec![Span::raw("Esc to cancel")]));
+    }
+    //also handling the playlist selection mode
+    if let Some(playlist_number_input) = selection 
+    {
+        footer.push(Spans::from(vec![
+            Span::raw("Select playlist by number: "),
+            Span::styled(
+                playlist_number_input,
+                Style::default()
+                    .fg(theme.playlist_number.0)
+                    .add_modifier(Modifier::BOLD),
+            ),]));
+    }
+    let list_rows = (visible_rows as usize).saturating_sub(footer.len()).max(1);
+    let cursor = cursor.min(playlists.len().saturating_sub(1));
+    let first = (cursor + 1).saturating_sub(list_rows);
+
+    let mut lines: Vec<Spans> = if playlists.is_empty() && playlists_loading 
+    {
+        vec![Spans::from(vec![Span::styled( "Loading playlists...", Style::default().fg(theme.playlist_name.0),)])]
+    } 
+    else if playlists.is_empty() 
     {
         vec![Spans::from(vec![Span::styled( "No playlists found.", Style::default().fg(theme.account_auth_failure.0),)])]
     } 
     //enumberating, displaying and indexing to the playists
     else 
     {
-        playlists.iter().enumerate().map(|(i, (title, id))| {
+        playlists.iter().enumerate().skip(first).take(list_rows).map(|(i, playlist)| {
+                let selected = i == cursor;
                 Spans::from(vec![
                     Span::styled(
-                        format!("{:02}. ", i + 1),
+                        format!("{}{:02}. ", if selected { "> " } else { "  " }, i + 1),
                         Style::default().fg(theme.playlist_number.0),
                     ),
                     Span::styled(
-                        title,
-                        Style::default()
-                            .fg(theme.playlist_name.0)
-                            .add_modifier(Modifier::BOLD),
+                        playlist.title.as_str(),
+                        if selected {
+                            Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                        } else {
+                            Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD)
+                        },
+                    ),
+                    Span::styled(
+                        if playlist.system { " [youtube]".to_string() } else if playlist.local { " [local]".to_string() } else { format!(" [{}]", playlist.privacy) },
+                        Style::default().fg(theme.playlist_number.0),
                     ),
-                    Span::raw(format!(" (ID: {})", id)),
+                    Span::styled(
+                        if !playlist.system && app_state::is_save_for_later(playlist, save_for_later) { " [save for later]" } else { "" },
+                        Style::default().fg(theme.playlist_number.0),
+                    ),
+                    Span::raw(format!(" (ID: {})", playlist.id)),
                 ])
             }).collect()
     };
-    //also handling the playlist selection mode
-    if playlist_selection_mode 
+    lines.extend(footer);
+    //showing how much of the list is there while pages are still coming in
+    //the total from youtube only counts the user's own playlists on youtube
+    let own = playlists.iter().filter(|playlist| !playlist.system && !playlist.local).count();
+    let title = if playlists_total as usize > own || playlists_loading 
     {
-        lines.push(Spans::from(vec![
-            Span::raw("Select playlist by number: "),
-            Span::styled(
-                playlist_number_input,
-                Style::default()
-                    .fg(theme.playlist_number.0)
-                    .add_modifier(Modifier::BOLD),
-            ),]));
-    }
+        format!("Playlists (loaded {} of {})", own, playlists_total)
+    } 
+    else 
+    {
+        "Playlists".to_string()
+    };
     //returning paragaph
     Paragraph::new(lines)
         .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true })
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Playlists")
+                .title(title)
                 .style(Style::default().fg(theme.playlist_box.0))
                 .border_type(tui::widgets::BorderType::Plain),
         )
@@ -151,52 +223,49 @@ pub fn render_accounts<'a>(theme: &Theme, messages: &'a [String]) -> Paragraph<'
 }
 //rendering command tab
 //commands are hardcoded for now, may create a .txt for it
+const COMMANDS: &[&str] = &[
+    "q: to quit",
+    "a: to show accounts",
+    "c: to show this commands message",
+    "h: to go to the home tab",
+    "p: to show playlists, Up/Down: to move through a long list of them, r: to reload them from youtube instead of the cache",
+    "in playlists: n: to create one, e: to rename, u: to change privacy, d: to delete, v: to open one and edit its videos",
+    "in playlists: N: to create a local playlist, kept on this machine, A: to add a video by ID or link to an open local playlist",
+    "without signing in (api_key in config.json): search, details, comments, channel pages, public and local playlists work, your playlists, subscriptions, ratings and changes need a: to sign in",
+    "in playlists: E: to export one to a .m3u, .json or .csv file, I: to import one of those or a list of links",
+    "l: to add a search result or history item to a playlist, +, - and =: to like, dislike or unrate one",
+    "i: to show your listening history, f: to filter it",
+    "t: to show listening stats, r: to change the date range, g: to group by day or week",
+    "o: to show subscriptions and their latest uploads, Enter: to play one, w: to mark it seen or unseen, W: to mark all seen, r: to reload",
+    "s: to search yt, f: to open the search filters (Up/Down to move, Enter to change, x to clear), m: to load more results",
+    "b: to bind a playlist, theme or history item to be the selected one",
+    "search results: videos and playlists are played, channels open their channel page",
+    "v: to open the channel of a search result (by number), of the highlighted video in an open playlist or of a subscription (by number)",
+    "u: to subscribe to or unsubscribe from the channel of a search result (by number) or of the open channel page",
+    "w: to save a search result (by number) to your save for later playlist",
+    "d: to show the details of a search result or history item (by number), or of the highlighted video in a playlist, the feed or a channel page",
+    "c in the details of a video: to read its comments, Enter: to show or hide replies, o: to sort by top or newest, m: to load more",
+    "R: to turn autoplay radio on or off, it keeps playing related tracks after a single video, R while it plays stops it",
+    "press 'q' while playing playlists to skip the current song",
+];
+
 pub fn render_commands<'a>(theme: &Theme) -> Paragraph<'a> 
 {
-    Paragraph::new(vec![
+    let mut lines = vec![
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::styled(
             "Current commands:",
             Style::default().fg(theme.command_text_even.0),
         )]),
         Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "q: to quit",
-            Style::default().fg(theme.command_text_odd.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "a: to show accounts",
-            Style::default().fg(theme.command_text_even.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "c: to show this commands message",
-            Style::default().fg(theme.command_text_odd.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "h: to go to the home tab",
-            Style::default().fg(theme.command_text_even.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "p: to show playlists",
-            Style::default().fg(theme.command_text_odd.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "v: to show videos in playlists",
-            Style::default().fg(theme.command_text_even.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "s: to search yt",
-            Style::default().fg(theme.command_text_odd.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "b: to bind a playlist or theme to be the selected playlist or theme",
-            Style::default().fg(theme.command_text_even.0),
-        )]),
-        Spans::from(vec![Span::styled(
-            "press 'q' while playing playlists to skip the current song",
-            Style::default().fg(theme.command_text_odd.0),
-        )]),
-    ])
+    ];
+    //alternating colors so the lines are easier to tell apart
+    lines.extend(COMMANDS.iter().enumerate().map(|(i, command)| {
+        let color = if i % 2 == 0 { theme.command_text_odd.0 } else { theme.command_text_even.0 };
+        Spans::from(vec![Span::styled(*command, Style::default().fg(color))])
+    }));
+
+    Paragraph::new(lines)
     .alignment(Alignment::Center)
     .block(
         Block::default()
@@ -206,8 +275,508 @@ pub fn render_commands<'a>(theme: &Theme) -> Paragraph<'a>
             .border_type(BorderType::Plain),
     )
 }
+//status bar under every tab, shows the last error or notice
+//the title keeps an eye on the remaining api quota and turns into a warning when it runs low
+pub fn render_status<'a>(theme: &Theme, status: &'a str, quota_remaining: u32, quota_limits: QuotaLimits, autoplay: bool, access: Access) -> Paragraph<'a> 
+{
+    //account features are gated while signed out, the title says why
+    let signed_out = match access 
+    {
+        Access::Account => "",
+        Access::ApiKey => " - not signed in, public videos only (API key)",
+        Access::SignedOut => " - not signed in, press 'a' to sign in",
+    };
+    let radio = format!("{}{}", if autoplay { " - autoplay radio on" } else { "" }, signed_out);
+    let quota_title = if quota_limits.is_low(quota_remaining) 
+    {
+        Span::styled(
+            format!("Status - quota low: {} of {} units left{}", quota_remaining, quota_limits.daily_limit, radio),
+            Style::default().fg(theme.account_auth_failure.0).add_modifier(Modifier::BOLD),
+        )
+    } 
+    else 
+    {
+        Span::raw(format!("Status - quota: {} of {} units left{}", quota_remaining, quota_limits.daily_limit, radio))
+    };
+    Paragraph::new(vec![Spans::from(vec![Span::styled(
+        status,
+        Style::default().fg(theme.account_auth_failure.0),
+    )])])
+    .alignment(Alignment::Left)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(quota_title)
+            .style(Style::default().fg(theme.tabs_basic.0))
+            .border_type(BorderType::Plain),
+    )
+}
+
+//the videos of an opened playlist, the highlighted one can be played, removed or moved
+//adding is the video id or link being typed for a local playlist
+//visible_rows is the inner height of the box, the list scrolls like the feed, the keys are in the title so they don't scroll away
+pub fn render_playlist_items<'a>(theme: &Theme, playlist: &'a Playlist, items: &'a [PlaylistEntry], cursor: usize, ratings: &HashMap<String, Rating>, adding: Option<&'a str>, visible_rows: u16) -> Paragraph<'a> 
+{
+    let mut lines: Vec<Spans> = if items.is_empty() 
+    {
+        vec![Spans::from(vec![Span::styled("This playlist is empty.", Style::default().fg(theme.account_auth_failure.0))])]
+    } 
+    else 
+    {
+        items.iter().enumerate().map(|(i, entry)| {
+            let selected = i == cursor;
+            Spans::from(vec![
+                Span::styled(
+                    format!("{}{:02}. ", if selected { "> " } else { "  " }, i + 1),
+                    Style::default().fg(theme.playlist_number.0),
+                ),
+                Span::styled(
+                    entry.title.as_str(),
+                    if selected {
+                        Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                    } else {
+                        Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD)
+                    },
+                ),
+                //videos added to a local playlist by id alone have no uploader
+                Span::raw(if entry.uploader.is_empty() { String::new() } else { format!(" by {}", entry.uploader) }),
+                Span::styled(
+                    ratings.get(&entry.video_id).map(|rating| rating.label()).unwrap_or_default(),
+                    Style::default().fg(theme.playlist_number.0),
+                ),
+            ])
+        }).collect()
+    };
+    let mut scroll = (cursor as u16).saturating_sub(visible_rows.saturating_sub(1));
+    if let Some(input) = adding 
+    {
+        lines.push(Spans::from(vec![Span::raw("")]));
+        lines.push(Spans::from(vec![
+            Span::raw("Video ID or link to add (Esc to cancel): "),
+            Span::styled(input, Style::default().fg(theme.playlist_number.0).add_modifier(Modifier::BOLD)),
+        ]));
+        //the prompt is the last line, it has to be on screen while typing
+        scroll = (lines.len() as u16).saturating_sub(visible_rows);
+    }
+    let keys = if playlist.local { ", A: add a video by its ID or link" } else { "" };
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.playlist_box.0))
+                .title(format!(
+                    "{} ({} videos) Up/Down: move, Enter: play, x: remove, [ and ]: move the video up or down, + like, - dislike, = remove rating, v: open channel, d: details{}, Esc: back",
+                    playlist.title,
+                    items.len(),
+                    keys,
+                ))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//asks which playlist a video should be added to, drawn over whatever tab is open
+pub fn render_add_to_playlist<'a>(theme: &Theme, pending: &'a PendingAdd, playlists: &'a [Playlist], loading: bool) -> Paragraph<'a> 
+{
+    let mut lines: Vec<Spans> = vec![
+        Spans::from(vec![
+            Span::raw("Add "),
+            Span::styled(pending.video.title.as_str(), Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD)),
+            Span::raw(" to which playlist?"),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+    ];
+    if playlists.is_empty() 
+    {
+        let text = if loading { "Loading playlists..." } else { "No playlists found." };
+        lines.push(Spans::from(vec![Span::styled(text, Style::default().fg(theme.account_auth_failure.0))]));
+    }
+    lines.extend(playlists.iter().enumerate().map(|(i, playlist)| {
+        Spans::from(vec![
+            Span::styled(format!("{:02}. ", i + 1), Style::default().fg(theme.playlist_number.0)),
+            Span::styled(playlist.title.as_str(), Style::default().fg(theme.playlist_name.0)),
+            Span::styled(
+                if playlist.system { " [youtube, can't be added to]" } else if playlist.local { " [local]" } else { "" },
+                Style::default().fg(theme.playlist_number.0),
+            ),
+        ])
+    }));
+    lines.push(Spans::from(vec![Span::raw("")]));
+    lines.push(Spans::from(vec![
+        Span::raw("Playlist number (Esc to cancel): "),
+        Span::styled(pending.input.as_str(), Style::default().fg(theme.playlist_number.0).add_modifier(Modifier::BOLD)),
+    ]));
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.playlist_box.0))
+                .title("Add to playlist")
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the channels the user is subscribed to, numbered so one can be opened with v
+//selection is the number being typed, if any
+pub fn render_subscriptions<'a>(theme: &Theme, subscriptions: &'a [Subscription], loading: bool, selection: Option<&'a str>) -> Paragraph<'a> 
+{
+    let mut lines: Vec<Spans> = if subscriptions.is_empty() 
+    {
+        let text = if loading { "Loading subscriptions..." } else { "No subscriptions found." };
+        vec![Spans::from(vec![Span::styled(text, Style::default().fg(theme.account_auth_failure.0))])]
+    } 
+    else 
+    {
+        subscriptions.iter().enumerate().map(|(i, subscription)| {
+            Spans::from(vec![
+                Span::styled(format!("{:02}. ", i + 1), Style::default().fg(theme.playlist_number.0)),
+                Span::styled(subscription.title.as_str(), Style::default().fg(theme.playlist_name.0)),
+            ])
+        }).collect()
+    };
+    if let Some(input) = selection {
+        lines.insert(0, Spans::from(vec![Span::styled(
+            format!("Open the channel of number: {}", input),
+            Style::default().fg(theme.search_number.0).add_modifier(Modifier::BOLD),
+        )]));
+    }
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.playlist_box.0))
+                .title(format!("Subscriptions ({})", subscriptions.len()))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the newest uploads of every subscription, unseen ones are bold and marked as new
+//visible_rows is the inner height of the box, the list scrolls so the highlighted upload stays on screen
+pub fn render_feed<'a>(theme: &Theme, feed: &'a [FeedVideo], seen: &HashSet<String>, cursor: usize, loading: bool, visible_rows: u16) -> Paragraph<'a> 
+{
+    let lines: Vec<Spans> = if feed.is_empty() 
+    {
+        let text = if loading { "Loading the latest uploads..." } else { "No uploads found." };
+        vec![Spans::from(vec![Span::styled(text, Style::default().fg(theme.account_auth_failure.0))])]
+    } 
+    else 
+    {
+        feed.iter().enumerate().map(|(i, video)| {
+            let is_seen = seen.contains(&video.video_id);
+            let mut title_style = Style::default().fg(theme.search_name.0);
+            if !is_seen {
+                title_style = title_style.add_modifier(Modifier::BOLD);
+            }
+            if i == cursor {
+                title_style = title_style.add_modifier(Modifier::REVERSED);
+            }
+            Spans::from(vec![
+                Span::styled(if i == cursor { "> " } else { "  " }, Style::default().fg(theme.search_number.0)),
+                Span::styled(if is_seen { "    " } else { "new " }, Style::default().fg(theme.search_duration.0)),
+                Span::styled(video.title.as_str(), title_style),
+                Span::styled(" by ", Style::default().fg(theme.search_uploader.0)),
+                Span::styled(video.channel_title.as_str(), Style::default().fg(theme.search_number.0)),
+                Span::raw(format!(" {}", video.published_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"))),
+            ])
+        }).collect()
+    };
+    let new = feed.iter().filter(|video| !seen.contains(&video.video_id)).count();
+    let scroll = (cursor as u16).saturating_sub(visible_rows.saturating_sub(1));
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.search_box.0))
+                .title(format!("Latest uploads ({} new)", new))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the header of a channel page: title, subscribers and description
+pub fn render_channel_header<'a>(theme: &Theme, channel: &'a Channel, subscribed: bool) -> Paragraph<'a> 
+{
+    let subscribers = match channel.subscriber_count {
+        Some(count) => format!("{} subscribers", format_count(count)),
+        None => "subscriber count hidden".to_string(),
+    };
+    let description = if channel.description.is_empty() { "No description." } else { channel.description.as_str() };
+    let lines = vec![
+        Spans::from(vec![
+            Span::styled(channel.title.as_str(), Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  {}", subscribers), Style::default().fg(theme.playlist_number.0)),
+            Span::styled(if subscribed { "  (subscribed)" } else { "" }, Style::default().fg(theme.search_uploader.0)),
+        ]),
+        Spans::from(vec![Span::raw(description)]),
+    ];
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.playlist_box.0))
+                .title("Channel (Up/Down: move, Tab: uploads or playlists, Enter: play, P: play uploads from here, d: details, u: subscribe or unsubscribe, Esc: close)")
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the newest uploads of the open channel, scrolled like the feed
+pub fn render_channel_uploads<'a>(theme: &Theme, uploads: &'a [PlaylistEntry], cursor: usize, focused: bool, visible_rows: u16) -> Paragraph<'a> 
+{
+    let lines: Vec<Spans> = if uploads.is_empty() 
+    {
+        vec![Spans::from(vec![Span::styled("No uploads.", Style::default().fg(theme.account_auth_failure.0))])]
+    } 
+    else 
+    {
+        uploads.iter().enumerate().map(|(i, entry)| {
+            let selected = focused && i == cursor;
+            Spans::from(vec![
+                Span::styled(
+                    format!("{}{:02}. ", if selected { "> " } else { "  " }, i + 1),
+                    Style::default().fg(theme.search_number.0),
+                ),
+                Span::styled(
+                    entry.title.as_str(),
+                    if selected {
+                        Style::default().fg(theme.search_name.0).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                    } else {
+                        Style::default().fg(theme.search_name.0)
+                    },
+                ),
+            ])
+        }).collect()
+    };
+    let scroll = (cursor as u16).saturating_sub(visible_rows.saturating_sub(1));
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(if focused { theme.tabs_highlight.0 } else { theme.search_box.0 }))
+                .title(format!("Uploads ({})", uploads.len()))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the public playlists of the open channel
+pub fn render_channel_playlists<'a>(theme: &Theme, playlists: &'a [Playlist], cursor: usize, focused: bool, visible_rows: u16) -> Paragraph<'a> 
+{
+    let lines: Vec<Spans> = if playlists.is_empty() 
+    {
+        vec![Spans::from(vec![Span::styled("No public playlists.", Style::default().fg(theme.account_auth_failure.0))])]
+    } 
+    else 
+    {
+        playlists.iter().enumerate().map(|(i, playlist)| {
+            let selected = focused && i == cursor;
+            Spans::from(vec![
+                Span::styled(
+                    format!("{}{:02}. ", if selected { "> " } else { "  " }, i + 1),
+                    Style::default().fg(theme.playlist_number.0),
+                ),
+                Span::styled(
+                    playlist.title.as_str(),
+                    if selected {
+                        Style::default().fg(theme.playlist_name.0).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                    } else {
+                        Style::default().fg(theme.playlist_name.0)
+                    },
+                ),
+            ])
+        }).collect()
+    };
+    let scroll = (cursor as u16).saturating_sub(visible_rows.saturating_sub(1));
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(if focused { theme.tabs_highlight.0 } else { theme.playlist_box.0 }))
+                .title(format!("Playlists ({})", playlists.len()))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the details pane of a video, the links and timestamps of the description are listed after it
+//links get a line of their own, so terminals that detect links let them be clicked
+pub fn render_video_details<'a>(theme: &Theme, details: &'a VideoDetails, input: &'a str, scroll: u16) -> Paragraph<'a> 
+{
+    let count = |count: Option<u64>| count.map(format_count).unwrap_or_else(|| "hidden".to_string());
+    let published = details
+        .published_at
+        .map(|date| date.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let label = Style::default().fg(theme.search_uploader.0);
+    let value = Style::default().fg(theme.search_number.0);
+
+    let mut lines = vec![
+        Spans::from(vec![Span::styled(details.title.as_str(), Style::default().fg(theme.search_name.0).add_modifier(Modifier::BOLD))]),
+        Spans::from(vec![Span::styled("by ", label), Span::styled(details.uploader.as_str(), value)]),
+        Spans::from(vec![
+            Span::styled("Published: ", label),
+            Span::styled(published, value),
+            Span::styled("  Length: ", label),
+            Span::styled(parse_iso8601_duration(&details.duration), value),
+            Span::styled("  Category: ", label),
+            Span::styled(details.category.as_str(), value),
+        ]),
+        Spans::from(vec![
+            Span::styled("Views: ", label),
+            Span::styled(count(details.view_count), value),
+            Span::styled("  Likes: ", label),
+            Span::styled(count(details.like_count), value),
+            Span::styled("  Comments: ", label),
+            Span::styled(count(details.comment_count), value),
+        ]),
+        Spans::from(vec![
+            Span::styled("Tags: ", label),
+            Span::styled(if details.tags.is_empty() { "none".to_string() } else { details.tags.join(", ") }, value),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+    ];
+    lines.extend(details.description.lines().map(|line| Spans::from(vec![Span::raw(line)])));
+
+    let links = description::extract_links(&details.description);
+    if !links.is_empty() 
+    {
+        lines.push(Spans::from(vec![Span::raw("")]));
+        lines.push(Spans::from(vec![Span::styled("Links:", label.add_modifier(Modifier::BOLD))]));
+        lines.extend(links.into_iter().map(|link| Spans::from(vec![Span::styled(link, value)])));
+    }
+    let timestamps = description::extract_timestamps(&details.description);
+    if !timestamps.is_empty() 
+    {
+        lines.push(Spans::from(vec![Span::raw("")]));
+        lines.push(Spans::from(vec![Span::styled("Timestamps:", label.add_modifier(Modifier::BOLD))]));
+        lines.extend(timestamps.iter().enumerate().map(|(i, timestamp)| {
+            Spans::from(vec![
+                Span::styled(format!("{:02}. ", i + 1), Style::default().fg(theme.search_duration.0)),
+                Span::styled(timestamp.to_string(), value),
+                Span::raw(format!(" {}", timestamp.label)),
+            ])
+        }));
+    }
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.search_box.0))
+                .title(format!(
+                    "Details (Enter: play, number and Enter: play from that timestamp, c: comments, Up/Down: scroll, Esc: close) {}",
+                    input
+                ))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the comments of a video, replies are indented under the comment they answer
+//width and height are the inner size of the box, long comments wrap so the scrolling has to count wrapped rows
+pub fn render_comments<'a>(theme: &Theme, view: &'a CommentsView, width: u16, height: u16) -> Paragraph<'a> 
+{
+    let mut lines: Vec<Spans> = vec![];
+    //the first row of the highlighted thread and how many rows it takes
+    let mut highlighted_rows = (0, 0);
+    let rows = |lines: &[Spans]| -> usize {
+        lines.iter().map(|line| line.width().max(1).div_ceil(width.max(1) as usize)).sum()
+    };
+
+    if view.threads.is_empty() 
+    {
+        lines.push(Spans::from(vec![Span::styled("No comments yet.", Style::default().fg(theme.account_auth_failure.0))]));
+    }
+    for (i, thread) in view.threads.iter().enumerate() 
+    {
+        let start = lines.len();
+        let selected = i == view.cursor;
+        let expanded = view.expanded.contains(&thread.id);
+        let mut header = comment_header(theme, &thread.comment, if selected { "> " } else { "  " }, selected);
+        if thread.reply_count > 0 {
+            header.0.push(Span::styled(
+                format!(" · {} {} ({})", thread.reply_count, if thread.reply_count == 1 { "reply" } else { "replies" }, if expanded { "Enter to hide" } else { "Enter to show" }),
+                Style::default().fg(theme.search_duration.0),
+            ));
+        }
+        lines.push(header);
+        lines.extend(thread.comment.text.lines().map(|line| Spans::from(vec![Span::raw(format!("  {}", line))])));
+        if expanded {
+            for reply in &thread.replies {
+                lines.push(comment_header(theme, reply, "    ↳ ", false));
+                lines.extend(reply.text.lines().map(|line| Spans::from(vec![Span::raw(format!("      {}", line))])));
+            }
+        }
+        lines.push(Spans::from(vec![Span::raw("")]));
+        if selected {
+            highlighted_rows = (rows(&lines[..start]), rows(&lines[start..]));
+        }
+    }
+    if view.next_page.is_some() 
+    {
+        lines.push(Spans::from(vec![Span::styled(
+            format!("{} comments loaded, press 'm' to load more...", view.threads.len()),
+            Style::default().fg(theme.search_uploader.0),
+        )]));
+    }
+    //the highlighted comment goes to the top once it wouldn't fit on screen any more
+    let (start, size) = highlighted_rows;
+    let scroll = if start + size > height as usize { start } else { 0 };
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll as u16, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.search_box.0))
+                .title(format!(
+                    "Comments on {}, {} (Up/Down: move, Enter: replies, o: sort, m: more, Esc: close)",
+                    view.video_title,
+                    view.order.label()
+                ))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the author, likes and date of a comment
+fn comment_header<'a>(theme: &Theme, comment: &'a Comment, prefix: &'a str, selected: bool) -> Spans<'a> 
+{
+    let mut author = Style::default().fg(theme.search_name.0).add_modifier(Modifier::BOLD);
+    if selected {
+        author = author.add_modifier(Modifier::REVERSED);
+    }
+    let date = comment
+        .published_at
+        .map(|date| date.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    Spans::from(vec![
+        Span::styled(prefix, Style::default().fg(theme.search_number.0)),
+        Span::styled(comment.author.as_str(), author),
+        Span::styled(format!(" · {} likes · {}", format_count(comment.like_count), date), Style::default().fg(theme.search_uploader.0)),
+    ])
+}
+
 //search rendering
-pub fn render_search<'a>(theme: &Theme,search_results: &'a [(String, String, String, String)],search_attempted: bool,search_selection_mode: bool,search_number_input: &'a str,) -> Paragraph<'a> 
+pub fn render_search<'a>(theme: &Theme,search_results: &'a [SearchResult],search_attempted: bool,has_more: bool,ratings: &HashMap<String, Rating>,subscribed: &HashMap<String, Option<String>>,selection: Option<(SelectionAction, &'a str)>,) -> Paragraph<'a> 
 {
     let mut lines: Vec<Spans> = if !search_attempted 
     {
@@ -225,31 +794,55 @@ pub fn render_search<'a>(theme: &Theme,search_results: &'a [(String, String, Str
     else 
     {
         search_results.iter().enumerate()
-            .map(|(i, (title, duration_raw, uploader, _id))| {
-                let duration = parse_iso8601_duration(duration_raw);
+            .map(|(i, result)| {
+                //videos show their length, playlists and channels get a label instead
+                let (label, title, uploader, suffix) = match result {
+                    SearchResult::Video(video) => {
+                        let rating = ratings.get(&video.id).map(|rating| rating.label()).unwrap_or_default();
+                        ("", video.title.as_str(), Some(video.uploader.as_str()), format!(" [{}]{}", parse_iso8601_duration(&video.duration), rating))
+                    }
+                    SearchResult::Playlist { title, uploader, .. } => ("[playlist] ", title.as_str(), Some(uploader.as_str()), String::new()),
+                    SearchResult::Channel { title, .. } => ("[channel] ", title.as_str(), None, String::new()),
+                };
 
-                Spans::from(vec![
+                let mut spans = vec![
                     Span::styled(
                         format!("{:02}. ", i + 1),
                         Style::default().fg(theme.search_number.0),
                     ),
+                    Span::styled(label, Style::default().fg(theme.search_duration.0)),
                     Span::styled(
                         title,
                         Style::default()
                             .fg(theme.search_name.0)
                             .add_modifier(Modifier::BOLD),
                     ),
-                    Span::styled(" by ", Style::default().fg(theme.search_uploader.0)),
-                    Span::styled(uploader, Style::default().fg(theme.search_number.0)),
-                    Span::styled(
-                        format!(" [{}]", duration),
-                        Style::default().fg(theme.search_name.0),),])}).collect()
+                ];
+                if let Some(uploader) = uploader {
+                    spans.push(Span::styled(" by ", Style::default().fg(theme.search_uploader.0)));
+                    spans.push(Span::styled(uploader, Style::default().fg(theme.search_number.0)));
+                }
+                //for channel results this comes right after the channel name
+                if matches!(subscribed.get(result.channel().0), Some(Some(_))) {
+                    spans.push(Span::styled(" (subscribed)", Style::default().fg(theme.search_uploader.0)));
+                }
+                spans.push(Span::styled(suffix, Style::default().fg(theme.search_duration.0)));
+                Spans::from(spans)}).collect()
     };
 
-    if search_selection_mode 
+    //more pages can be loaded as long as youtube handed back a page token
+    if has_more 
+    {
+        lines.push(Spans::from(Span::styled(
+            format!("{} results loaded, press 'm' to load more...", search_results.len()),
+            Style::default().fg(theme.search_uploader.0),
+        )));
+    }
+
+    if let Some((action, search_number_input)) = selection 
     {
         lines.push(Spans::from(vec![
-            Span::raw("Select video by number: "),
+            Span::raw(action.prompt()),
             Span::styled(
                 search_number_input,
                 Style::default().fg(theme.search_number.0).add_modifier(Modifier::BOLD),),]));
@@ -266,6 +859,254 @@ pub fn render_search<'a>(theme: &Theme,search_results: &'a [(String, String, Str
                 .border_type(tui::widgets::BorderType::Plain),
         )
 }
+//the search filter panel, the highlighted row is changed with Enter
+pub fn render_search_filters<'a>(theme: &Theme, filters: &SearchFilters, cursor: usize, editing: bool, edit_input: &'a str) -> Paragraph<'a> 
+{
+    let mut lines: Vec<Spans> = FILTER_FIELDS
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let selected = i == cursor;
+            let value = if selected && editing {
+                format!("{}_", edit_input)
+            } else {
+                let value = filters.value(*field);
+                if value.is_empty() { "-".to_string() } else { value }
+            };
+            let name_style = if selected {
+                Style::default().fg(theme.search_name.0).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.search_uploader.0)
+            };
+            Spans::from(vec![
+                Span::styled(if selected { "> " } else { "  " }, Style::default().fg(theme.search_number.0)),
+                Span::styled(format!("{:<16}", field.label()), name_style),
+                Span::styled(value, Style::default().fg(theme.search_number.0)),
+            ])
+        })
+        .collect();
+
+    lines.push(Spans::from(vec![Span::raw("")]));
+    let help = if editing {
+        "Enter: save, Esc: cancel, leave empty to clear (dates are YYYY-MM-DD)"
+    } else {
+        "Up/Down: move, Enter: change or edit, x: clear all, f or Esc: close"
+    };
+    lines.push(Spans::from(vec![Span::styled(help, Style::default().fg(theme.search_box.0))]));
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Search Filters")
+                .style(Style::default().fg(theme.search_box.0))
+                .border_type(BorderType::Plain),
+        )
+}
+//history rendering, newest plays first and narrowed down by the filter
+pub fn render_history<'a>(theme: &Theme, entries: &'a [HistoryEntry], filter_input: &'a str, history_typing: bool, selection: Option<(SelectionAction, &'a str)>,) -> Paragraph<'a> 
+{
+    let mut lines: Vec<Spans> = vec![Spans::from(vec![
+        Span::raw("Filter: "),
+        Span::styled(filter_input, Style::default().fg(theme.search_number.0).add_modifier(Modifier::BOLD)),
+        Span::raw(if history_typing { "_" } else { "" }),
+    ])];
+
+    let filtered = history::filter_history(entries, filter_input);
+    if filtered.is_empty() 
+    {
+        lines.push(Spans::from(Span::styled(
+            "Nothing played yet.",
+            Style::default().fg(theme.account_auth_failure.0),
+        )));
+    } 
+    else 
+    {
+        lines.extend(filtered.into_iter().enumerate().map(|(i, entry)| {
+            let source = match entry.source {
+                PlaySource::Search => "search",
+                PlaySource::Playlist => "playlist",
+                PlaySource::History => "history",
+                PlaySource::Feed => "feed",
+                PlaySource::Channel => "channel",
+                PlaySource::Details => "details",
+                PlaySource::Radio => "radio",
+            };
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:02}. ", i + 1),
+                    Style::default().fg(theme.search_number.0),
+                ),
+                Span::styled(
+                    entry.title.as_str(),
+                    Style::default()
+                        .fg(theme.search_name.0)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" by ", Style::default().fg(theme.search_uploader.0)),
+                Span::styled(entry.uploader.as_str(), Style::default().fg(theme.search_number.0)),
+                Span::styled(
+                    format!(" [{}]", format_seconds(entry.listened_secs)),
+                    Style::default().fg(theme.search_duration.0),
+                ),
+                Span::raw(format!(" {} ({})", entry.started_at.format("%Y-%m-%d %H:%M"), source)),
+            ])
+        }));
+    }
+
+    if let Some((action, history_number_input)) = selection 
+    {
+        lines.push(Spans::from(vec![
+            Span::raw(action.prompt()),
+            Span::styled(
+                history_number_input,
+                Style::default().fg(theme.search_number.0).add_modifier(Modifier::BOLD),),]));
+    }
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("History")
+                .style(Style::default().fg(theme.search_box.0))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//the stats header with the selected range and the totals in it
+pub fn render_stats_summary<'a>(theme: &Theme, stats: &ListeningStats, range: StatsRange, grouping: StatsGrouping) -> Paragraph<'a> 
+{
+    Paragraph::new(vec![Spans::from(vec![
+        Span::styled(
+            format!("Stats for the {} ", range.label()),
+            Style::default().fg(theme.home_text.0).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("({}): ", grouping.label()),
+            Style::default().fg(theme.search_uploader.0),
+        ),
+        Span::styled(
+            format!("{} plays, {} listened", stats.plays, format_seconds(stats.total_secs)),
+            Style::default().fg(theme.search_duration.0),
+        ),
+    ])])
+    .alignment(Alignment::Left)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Stats")
+            .style(Style::default().fg(theme.search_box.0))
+            .border_type(BorderType::Plain),
+    )
+}
+
+//minutes listened per day or week as a bar chart
+pub fn render_stats_chart<'a>(theme: &Theme, bars: &'a [(&'a str, u64)], grouping: StatsGrouping) -> BarChart<'a> 
+{
+    BarChart::default()
+        .data(bars)
+        .bar_width(5)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(theme.search_name.0))
+        .value_style(Style::default().fg(theme.search_box.0).bg(theme.search_name.0))
+        .label_style(Style::default().fg(theme.search_number.0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Minutes listened {}", grouping.label()))
+                .style(Style::default().fg(theme.search_box.0))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//daily listening trend, one column per day
+pub fn render_stats_sparkline<'a>(theme: &Theme, daily_minutes: &'a [u64]) -> Sparkline<'a> 
+{
+    Sparkline::default()
+        .data(daily_minutes)
+        .style(Style::default().fg(theme.search_duration.0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Daily trend")
+                .style(Style::default().fg(theme.search_box.0))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//most listened channels by time spent on them
+pub fn render_stats_top_channels<'a>(theme: &Theme, top_channels: &'a [(String, u64)]) -> Paragraph<'a> 
+{
+    let lines: Vec<Spans> = top_channels.iter().enumerate().map(|(i, (channel, secs))| {
+        Spans::from(vec![
+            Span::styled(format!("{:02}. ", i + 1), Style::default().fg(theme.search_number.0)),
+            Span::styled(channel.as_str(), Style::default().fg(theme.search_name.0).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" [{}]", format_seconds(*secs)), Style::default().fg(theme.search_duration.0)),
+        ])
+    }).collect();
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Top channels")
+                .style(Style::default().fg(theme.search_box.0))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//most played tracks by number of plays
+pub fn render_stats_top_tracks<'a>(theme: &Theme, top_tracks: &'a [(String, usize)]) -> Paragraph<'a> 
+{
+    let lines: Vec<Spans> = top_tracks.iter().enumerate().map(|(i, (title, plays))| {
+        Spans::from(vec![
+            Span::styled(format!("{:02}. ", i + 1), Style::default().fg(theme.search_number.0)),
+            Span::styled(title.as_str(), Style::default().fg(theme.search_name.0).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" [{} plays]", plays), Style::default().fg(theme.search_duration.0)),
+        ])
+    }).collect();
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Top tracks")
+                .style(Style::default().fg(theme.search_box.0))
+                .border_type(BorderType::Plain),
+        )
+}
+
+//formatting a number of seconds the same way video durations are shown
+fn format_seconds(secs: u64) -> String 
+{
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+//shortening big counts the way youtube does, like 1.2M
+fn format_count(count: u64) -> String 
+{
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}K", count as f64 / 1_000.0),
+        1_000_000..=999_999_999 => format!("{:.1}M", count as f64 / 1_000_000.0),
+        _ => format!("{:.1}B", count as f64 / 1_000_000_000.0),
+    }
+}
+
 //parsing video duration
 fn parse_iso8601_duration(duration: &str) -> String 
 {
@@ -274,14 +1115,12 @@ fn parse_iso8601_duration(duration: &str) -> String
     let mut seconds = 0;
 
     let mut num = String::new();
-    let mut chars = duration.chars().peekable();
-
-    while let Some(c) = chars.next() {
+    for c in duration.chars() {
         if c == 'P' || c == 'T' {
             continue;
         }
 
-        if c.is_digit(10) {
+        if c.is_ascii_digit() {
             num.push(c);
         } else {
             match c {
@@ -315,11 +1154,12 @@ fn parse_iso8601_duration(duration: &str) -> String
     }
 }
 
-pub fn render_search_prompt<'a>(user_input: &'a str) -> Paragraph<'a> 
+pub fn render_search_prompt<'a>(user_input: &'a str, filters: &SearchFilters) -> Paragraph<'a> 
 {
     Paragraph::new(vec![
         Spans::from(vec![Span::raw("Search YouTube:")]),
         Spans::from(vec![Span::raw(user_input)]),
+        Spans::from(vec![Span::raw(format!("Filters: {}", filters.summary()))]),
     ])
     .alignment(Alignment::Left)
     .block(
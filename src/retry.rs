@@ -0,0 +1,36 @@
+//retry decides how long to wait before trying a failed API call again
+//the wait doubles with every attempt and is randomized ("full jitter"),
+//so a lot of clients failing at once don't all come back at the same moment
+use rand::Rng;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    //retries after the first attempt, 0 turns retrying off
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    //the wait before retry number `attempt` (starting at 1)
+    //a Retry-After from the server wins, unless it is longer than we are willing to wait
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt > self.max_retries {
+            return None;
+        }
+        if let Some(retry_after) = retry_after {
+            return if retry_after <= self.max_delay {
+                Some(retry_after)
+            } else {
+                None
+            };
+        }
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        let millis = rand::thread_rng().gen_range(0..=exponential.as_millis() as u64);
+        Some(Duration::from_millis(millis))
+    }
+}
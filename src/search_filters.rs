@@ -0,0 +1,236 @@
+//search_filters holds the extra parameters the user can set for searching
+//they are kept in the app state, so they stay the same for every search in a session
+use chrono::NaiveDate;
+
+//the possible values of the choice filters, the first one is the api default
+//"all" isn't an api value, it searches for every kind of result at once
+const KINDS: &[&str] = &["video", "all", "playlist", "channel"];
+const ORDERS: &[&str] = &["relevance", "date", "viewCount", "rating"];
+const DURATIONS: &[&str] = &["any", "short", "medium", "long"];
+const CAPTIONS: &[&str] = &["any", "closedCaption", "none"];
+const DEFINITIONS: &[&str] = &["any", "high", "standard"];
+const SAFE_SEARCH: &[&str] = &["moderate", "none", "strict"];
+
+//the rows of the filter panel, in the order they are shown
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FilterField {
+    Kind,
+    Order,
+    Duration,
+    PublishedAfter,
+    PublishedBefore,
+    Captions,
+    Definition,
+    Channel,
+    SafeSearch,
+    Region,
+    Language,
+}
+
+pub const FILTER_FIELDS: [FilterField; 11] = [
+    FilterField::Kind,
+    FilterField::Order,
+    FilterField::Duration,
+    FilterField::PublishedAfter,
+    FilterField::PublishedBefore,
+    FilterField::Captions,
+    FilterField::Definition,
+    FilterField::Channel,
+    FilterField::SafeSearch,
+    FilterField::Region,
+    FilterField::Language,
+];
+
+impl FilterField {
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterField::Kind => "Type",
+            FilterField::Order => "Order",
+            FilterField::Duration => "Duration",
+            FilterField::PublishedAfter => "Uploaded after",
+            FilterField::PublishedBefore => "Uploaded before",
+            FilterField::Captions => "Captions",
+            FilterField::Definition => "Definition",
+            FilterField::Channel => "Channel ID",
+            FilterField::SafeSearch => "Safe search",
+            FilterField::Region => "Region",
+            FilterField::Language => "Language",
+        }
+    }
+
+    //text fields are typed in, the others cycle through their choices
+    pub fn is_text(self) -> bool {
+        matches!(
+            self,
+            FilterField::PublishedAfter
+                | FilterField::PublishedBefore
+                | FilterField::Channel
+                | FilterField::Region
+                | FilterField::Language
+        )
+    }
+
+    fn choices(self) -> &'static [&'static str] {
+        match self {
+            FilterField::Kind => KINDS,
+            FilterField::Order => ORDERS,
+            FilterField::Duration => DURATIONS,
+            FilterField::Captions => CAPTIONS,
+            FilterField::Definition => DEFINITIONS,
+            FilterField::SafeSearch => SAFE_SEARCH,
+            _ => &[],
+        }
+    }
+}
+
+//choice filters are stored as an index into their list of choices
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    pub kind: usize,
+    pub order: usize,
+    pub duration: usize,
+    pub published_after: Option<NaiveDate>,
+    pub published_before: Option<NaiveDate>,
+    pub captions: usize,
+    pub definition: usize,
+    pub channel_id: String,
+    pub safe_search: usize,
+    pub region_code: String,
+    pub relevance_language: String,
+}
+
+impl SearchFilters {
+    fn choice(&self, field: FilterField) -> Option<usize> {
+        match field {
+            FilterField::Kind => Some(self.kind),
+            FilterField::Order => Some(self.order),
+            FilterField::Duration => Some(self.duration),
+            FilterField::Captions => Some(self.captions),
+            FilterField::Definition => Some(self.definition),
+            FilterField::SafeSearch => Some(self.safe_search),
+            _ => None,
+        }
+    }
+
+    //the value of a field as it is shown in the panel, empty if it isn't set
+    pub fn value(&self, field: FilterField) -> String {
+        if let Some(idx) = self.choice(field) {
+            return field.choices()[idx].to_string();
+        }
+        match field {
+            FilterField::PublishedAfter => self.published_after.map(|d| d.to_string()).unwrap_or_default(),
+            FilterField::PublishedBefore => self.published_before.map(|d| d.to_string()).unwrap_or_default(),
+            FilterField::Channel => self.channel_id.clone(),
+            FilterField::Region => self.region_code.clone(),
+            FilterField::Language => self.relevance_language.clone(),
+            _ => String::new(),
+        }
+    }
+
+    //moves a choice filter on to its next value
+    pub fn cycle(&mut self, field: FilterField) {
+        let len = field.choices().len();
+        let slot = match field {
+            FilterField::Kind => &mut self.kind,
+            FilterField::Order => &mut self.order,
+            FilterField::Duration => &mut self.duration,
+            FilterField::Captions => &mut self.captions,
+            FilterField::Definition => &mut self.definition,
+            FilterField::SafeSearch => &mut self.safe_search,
+            _ => return,
+        };
+        *slot = (*slot + 1) % len;
+    }
+
+    //sets a text filter from what the user typed, an empty input clears it
+    pub fn set_text(&mut self, field: FilterField, input: &str) -> Result<(), String> {
+        let input = input.trim();
+        let parse_date = |input: &str| -> Result<Option<NaiveDate>, String> {
+            if input.is_empty() {
+                return Ok(None);
+            }
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| format!("'{}' is not a date, use YYYY-MM-DD", input))
+        };
+        match field {
+            FilterField::PublishedAfter => self.published_after = parse_date(input)?,
+            FilterField::PublishedBefore => self.published_before = parse_date(input)?,
+            FilterField::Channel => self.channel_id = input.to_string(),
+            FilterField::Region => {
+                if !input.is_empty() && (input.len() != 2 || !input.chars().all(|c| c.is_ascii_alphabetic())) {
+                    return Err(format!("'{}' is not a two letter country code like US or HU", input));
+                }
+                self.region_code = input.to_uppercase();
+            }
+            FilterField::Language => self.relevance_language = input.to_lowercase(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    //true if only videos are searched for, the video filters are ignored otherwise
+    pub fn videos_only(&self) -> bool {
+        self.kind == 0
+    }
+
+    //the search endpoint parameters for the type and every filter that isn't left at its default
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let kind = match KINDS[self.kind] {
+            "all" => "video,playlist,channel",
+            kind => kind,
+        };
+        let mut pairs = vec![("type", kind.to_string())];
+        let mut choices = vec![
+            ("order", FilterField::Order, self.order),
+            ("safeSearch", FilterField::SafeSearch, self.safe_search),
+        ];
+        //youtube rejects these unless the type is exactly video
+        if self.videos_only() {
+            choices.extend(vec![
+                ("videoDuration", FilterField::Duration, self.duration),
+                ("videoCaption", FilterField::Captions, self.captions),
+                ("videoDefinition", FilterField::Definition, self.definition),
+            ]);
+        }
+        for (name, field, idx) in choices {
+            if idx != 0 {
+                pairs.push((name, field.choices()[idx].to_string()));
+            }
+        }
+        //the api wants full RFC 3339 timestamps, the day is taken as a whole in UTC
+        if let Some(date) = self.published_after {
+            pairs.push(("publishedAfter", format!("{}T00:00:00Z", date)));
+        }
+        if let Some(date) = self.published_before {
+            pairs.push(("publishedBefore", format!("{}T23:59:59Z", date)));
+        }
+        if !self.channel_id.is_empty() {
+            pairs.push(("channelId", self.channel_id.clone()));
+        }
+        if !self.region_code.is_empty() {
+            pairs.push(("regionCode", self.region_code.clone()));
+        }
+        if !self.relevance_language.is_empty() {
+            pairs.push(("relevanceLanguage", self.relevance_language.clone()));
+        }
+        pairs
+    }
+
+    //a short line with the filters in use, shown under the search prompt
+    pub fn summary(&self) -> String {
+        let active: Vec<String> = FILTER_FIELDS
+            .iter()
+            .filter(|field| match self.choice(**field) {
+                Some(idx) => idx != 0,
+                None => !self.value(**field).is_empty(),
+            })
+            .map(|field| format!("{}: {}", field.label(), self.value(*field)))
+            .collect();
+        if active.is_empty() {
+            "none".to_string()
+        } else {
+            active.join(", ")
+        }
+    }
+}
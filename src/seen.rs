@@ -0,0 +1,25 @@
+//seen remembers which uploads of the feed were already watched or marked as seen
+//it only lives on this machine, youtube doesn't tell us what was watched
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+pub const SEEN_FILE: &str = "seen.json";
+
+//the video ids marked as seen, a missing file means nothing was seen yet
+pub fn load_seen() -> Result<HashSet<String>> {
+    if !Path::new(SEEN_FILE).exists() {
+        return Ok(HashSet::new());
+    }
+    let json = fs::read_to_string(SEEN_FILE)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+pub fn save_seen(seen: &HashSet<String>) -> Result<()> {
+    //sorted so the file doesn't change on every save
+    let mut ids: Vec<&String> = seen.iter().collect();
+    ids.sort();
+    fs::write(SEEN_FILE, serde_json::to_string_pretty(&ids)?)?;
+    Ok(())
+}
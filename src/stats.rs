@@ -0,0 +1,182 @@
+//stats turns the local play history into numbers for the Stats tab
+//everything is computed from history.jsonl, so nothing here talks to the API
+use crate::history::HistoryEntry;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use std::collections::HashMap;
+
+//how far back the stats look
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatsRange {
+    Week,
+    Month,
+    Year,
+    AllTime,
+}
+
+impl StatsRange {
+    //cycling through the ranges with a single key
+    pub fn next(self) -> Self {
+        match self {
+            StatsRange::Week => StatsRange::Month,
+            StatsRange::Month => StatsRange::Year,
+            StatsRange::Year => StatsRange::AllTime,
+            StatsRange::AllTime => StatsRange::Week,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatsRange::Week => "last 7 days",
+            StatsRange::Month => "last 30 days",
+            StatsRange::Year => "last 365 days",
+            StatsRange::AllTime => "all time",
+        }
+    }
+
+    fn days(self) -> Option<i64> {
+        match self {
+            StatsRange::Week => Some(7),
+            StatsRange::Month => Some(30),
+            StatsRange::Year => Some(365),
+            StatsRange::AllTime => None,
+        }
+    }
+}
+
+//whether the bar chart shows one bar per day or per week
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatsGrouping {
+    Day,
+    Week,
+}
+
+impl StatsGrouping {
+    pub fn toggle(self) -> Self {
+        match self {
+            StatsGrouping::Day => StatsGrouping::Week,
+            StatsGrouping::Week => StatsGrouping::Day,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatsGrouping::Day => "per day",
+            StatsGrouping::Week => "per week",
+        }
+    }
+}
+
+pub struct ListeningStats {
+    pub plays: usize,
+    pub total_secs: u64,
+    //(label, minutes listened) for the bar chart, oldest first
+    pub buckets: Vec<(String, u64)>,
+    //minutes listened for every day of the range, for the sparkline
+    pub daily_minutes: Vec<u64>,
+    //(channel, seconds listened), most listened first
+    pub top_channels: Vec<(String, u64)>,
+    //(title, number of plays), most played first
+    pub top_tracks: Vec<(String, usize)>,
+}
+
+const TOP_COUNT: usize = 10;
+
+pub fn compute_stats(
+    entries: &[HistoryEntry],
+    range: StatsRange,
+    grouping: StatsGrouping,
+    now: DateTime<Local>,
+) -> ListeningStats {
+    let today = now.date_naive();
+    let in_range: Vec<&HistoryEntry> = entries
+        .iter()
+        .filter(|entry| match range.days() {
+            Some(days) => entry.started_at.date_naive() > today - Duration::days(days),
+            None => true,
+        })
+        .collect();
+
+    //the first day shown, for all time this is the day of the oldest play
+    let first_day = match range.days() {
+        Some(days) => today - Duration::days(days - 1),
+        None => in_range
+            .iter()
+            .map(|entry| entry.started_at.date_naive())
+            .min()
+            .unwrap_or(today),
+    };
+
+    let mut secs_per_day: HashMap<NaiveDate, u64> = HashMap::new();
+    let mut secs_per_channel: HashMap<&str, u64> = HashMap::new();
+    let mut plays_per_track: HashMap<&str, (&str, usize)> = HashMap::new();
+    let mut total_secs = 0;
+
+    for entry in &in_range {
+        total_secs += entry.listened_secs;
+        *secs_per_day.entry(entry.started_at.date_naive()).or_insert(0) += entry.listened_secs;
+        *secs_per_channel.entry(entry.uploader.as_str()).or_insert(0) += entry.listened_secs;
+        plays_per_track
+            .entry(entry.video_id.as_str())
+            .or_insert((entry.title.as_str(), 0))
+            .1 += 1;
+    }
+
+    let days: Vec<NaiveDate> = first_day
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .collect();
+    let daily_minutes = days
+        .iter()
+        .map(|day| secs_per_day.get(day).copied().unwrap_or(0) / 60)
+        .collect();
+
+    let buckets = match grouping {
+        StatsGrouping::Day => days
+            .iter()
+            .map(|day| {
+                let secs = secs_per_day.get(day).copied().unwrap_or(0);
+                (day.format("%m-%d").to_string(), secs / 60)
+            })
+            .collect(),
+        StatsGrouping::Week => {
+            //weeks start on monday and are labelled by that monday
+            let mut weeks: Vec<(NaiveDate, u64)> = vec![];
+            for day in &days {
+                let monday = *day - Duration::days(day.weekday().num_days_from_monday() as i64);
+                let secs = secs_per_day.get(day).copied().unwrap_or(0);
+                match weeks.last_mut() {
+                    Some((start, total)) if *start == monday => *total += secs,
+                    _ => weeks.push((monday, secs)),
+                }
+            }
+            weeks
+                .into_iter()
+                .map(|(monday, secs)| (monday.format("%m-%d").to_string(), secs / 60))
+                .collect()
+        }
+    };
+
+    let mut top_channels: Vec<(String, u64)> = secs_per_channel
+        .into_iter()
+        .filter(|(channel, _)| !channel.is_empty())
+        .map(|(channel, secs)| (channel.to_string(), secs))
+        .collect();
+    top_channels.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_channels.truncate(TOP_COUNT);
+
+    let mut top_tracks: Vec<(String, usize)> = plays_per_track
+        .into_values()
+        .map(|(title, plays)| (title.to_string(), plays))
+        .collect();
+    top_tracks.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_tracks.truncate(TOP_COUNT);
+
+    ListeningStats {
+        plays: in_range.len(),
+        total_secs,
+        buckets,
+        daily_minutes,
+        top_channels,
+        top_tracks,
+    }
+}
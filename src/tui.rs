@@ -4,17 +4,30 @@ use crate::app_state;
 use crate::app_state::AppState;
 use crate::app_state::Event;
 use crate::app_state::MenuItem;
+use crate::app_state::SelectionAction;
 use crate::authenticate::authenticate;
+use crate::cache;
 use crate::colors::Theme;
+use crate::config;
+use crate::errors;
+use crate::local_playlists::{self, LocalPlaylist};
 use crate::render;
+use crate::search_filters::SearchFilters;
+use crate::seen;
+use crate::stats::{self, StatsGrouping, StatsRange};
+use crate::utilities;
+use crate::youtube::{Access, YouTubeClient};
 use anyhow::Result;
-use crossterm::{event::{self, Event as CEvent},terminal::enable_raw_mode,};
+use crossterm::{cursor::MoveTo, event::{self, Event as CEvent, KeyCode}, execute, style::Print, terminal::{self, enable_raw_mode}};
+use std::collections::{HashMap, HashSet};
 use std::io;
-use std::sync::mpsc;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
-use tui::{backend::CrosstermBackend,layout::{Constraint, Direction, Layout},style::{Modifier, Style},text::{Span, Spans},widgets::{Block, Borders, Tabs},Terminal,};
+use tui::{backend::CrosstermBackend,layout::{Constraint, Direction, Layout},style::{Modifier, Style},text::{Span, Spans},widgets::{Block, Borders, Clear, Tabs},Terminal,};
 
 impl From<MenuItem> for usize {
     //numbering the menu items with a usize (variable used for indexing collections)
@@ -22,40 +35,142 @@ impl From<MenuItem> for usize {
         match input {
             MenuItem::Account => 0,
             MenuItem::Commands => 1,
-            MenuItem::Home => 2,
-            MenuItem::Playlists => 3,
-            MenuItem::Search => 4,
+            MenuItem::History => 2,
+            MenuItem::Home => 3,
+            MenuItem::Playlists => 4,
+            MenuItem::Search => 5,
+            MenuItem::Stats => 6,
+            MenuItem::Subscriptions => 7,
         }
     }
 }
+//writes a message over the text of the status bar while the loop is busy and can't draw a frame
+fn draw_status_line(message: &str) -> crossterm::Result<()> {
+    let (width, height) = terminal::size()?;
+    //the layout has a margin of 2 and the status bar takes the last 3 rows, its text is inside the border
+    let inner = width.saturating_sub(6) as usize;
+    let text: String = message.chars().take(inner).collect();
+    execute!(io::stdout(), MoveTo(3, height.saturating_sub(4)), Print(format!("{:<width$}", text, width = inner)))
+}
 //main function
 pub async fn tui_render() -> Result<()> {
+    //settings from config.json, the api client is built from them
+    //a broken config.json shouldn't keep the tui from starting, the defaults are used and the error is shown
+    let (config, config_error) = match config::load_config() {
+        Ok(config) => (config, None),
+        Err(e) => (config::Config::default(), Some(format!("{:#}, using the default settings.", e))),
+    };
+    //sender and reciever events initialized,these allow communication between the user and the app
+    let (tx, rx) = mpsc::channel(); 
+    //the api client reports retries through the same channel
+    let tx_retry = tx.clone();
+    //set while a key is handled, the api calls it makes hold up the loop so it can't draw their retries
+    let handling_input = Arc::new(AtomicBool::new(false));
+    //set when a retry was written straight onto the status bar, the next frame is then drawn from scratch
+    let status_drawn = Arc::new(AtomicBool::new(false));
+    let on_retry = {
+        let handling_input = Arc::clone(&handling_input);
+        let status_drawn = Arc::clone(&status_drawn);
+        move |retry_status: Option<String>| {
+            if let (true, Some(message)) = (handling_input.load(Ordering::SeqCst), &retry_status) {
+                if draw_status_line(message).is_ok() {
+                    status_drawn.store(true, Ordering::SeqCst);
+                }
+            }
+            let _ = tx_retry.send(Event::RetryStatus(retry_status));
+        }
+    };
     //creating a mutable (!!) appstate instance
     let mut state = AppState {
         //inital app state
         messages: vec![],
+        status: String::new(),
+        retry_status: None,
         authenticated: false,
         active_menu_item: MenuItem::Home,
         playlists: vec![],
+        playlists_total: 0,
+        playlists_requested: false,
+        playlists_force_refresh: false,
+        playlists_loading: false,
+        playlists_loaded: false,
+        playlists_generation: 0,
         search_result: vec![],
+        search_query: String::new(),
+        search_query_filters: SearchFilters::default(),
+        search_next_page: None,
         search_attempted: false,
         playlist_number_input: String::new(),
         playlist_selection_mode: false,
+        playlist_cursor: 0,
+        playlist_form: None,
+        playlist_open: None,
+        playlist_items: vec![],
+        playlist_item_cursor: 0,
+        local_video_input: None,
+        selection_action: SelectionAction::Play,
+        ratings: HashMap::new(),
+        subscribed: HashMap::new(),
+        add_to_playlist: None,
         search_input: String::new(),
         search_typing: false,
         search_number_input: String::new(),
+        search_filters: SearchFilters::default(),
+        search_filters_open: false,
+        search_filter_cursor: 0,
+        search_filter_editing: false,
+        search_filter_input: String::new(),
+        history: vec![],
+        history_filter_input: String::new(),
+        history_typing: false,
+        history_selection_mode: false,
+        history_number_input: String::new(),
+        subscriptions: vec![],
+        subscriptions_requested: false,
+        subscriptions_force_refresh: false,
+        subscriptions_loading: false,
+        subscriptions_generation: 0,
+        feed: vec![],
+        feed_cursor: 0,
+        seen: HashSet::new(),
+        subscription_selection_mode: false,
+        subscription_number_input: String::new(),
+        channel_open: None,
+        channel_uploads: vec![],
+        channel_playlists: vec![],
+        channel_upload_cursor: 0,
+        channel_playlist_cursor: 0,
+        channel_playlists_focused: false,
+        details_open: None,
+        details_scroll: 0,
+        details_input: String::new(),
+        comments: None,
+        autoplay: config.autoplay,
+        stats_range: StatsRange::Week,
+        stats_grouping: StatsGrouping::Day,
         themes: vec![],
         search_selection_mode: false,
         theme_selection_mode: false,
         selected_theme: Theme::new(),
         theme_number_input: String::new(),
         theme_selected_path: "themes/blue_theme.json".to_string(),
+        youtube: YouTubeClient::new(&config, Arc::new(app_state::SignedInAccount), on_retry),
+        config,
     };
 
     state.selected_theme = app_state::load_and_set_theme_from_file(&state.theme_selected_path)?; 
+    match seen::load_seen() {
+        Ok(seen) => state.seen = seen,
+        Err(e) => state.status = format!("Failed to load seen videos: {}", e),
+    }
+    //old responses would only be revalidated, so they aren't worth keeping around
+    if let Err(e) = cache::evict_expired(Path::new(&state.config.cache_dir)) {
+        state.status = format!("Failed to clean up the cache: {}", e);
+    }
+    if let Some(message) = config_error {
+        state.status = message;
+    }
     enable_raw_mode().expect("can run in raw mode"); //putting terminal in raw mode
-    //sender and reciever events initialized,these allow communication between the user and the app
-    let (tx, rx) = mpsc::channel(); 
     let tick_rate = Duration::from_millis(200);
     //cloning tx to avoid ownership issues
     let tx_input = tx.clone();
@@ -65,8 +180,17 @@ pub async fn tui_render() -> Result<()> {
     let backend = CrosstermBackend::new(stdout); 
     let mut terminal = Terminal::new(backend)?; 
     terminal.clear()?; 
-    //collection of menuitems
-    let menu_titles = vec!["Account", "Commands", "Home", "Playlists", "Search"]; 
+    //collection of menuitems with the position of the letter that opens them
+    let menu_titles = [
+        ("Account", 0),
+        ("Commands", 0),
+        ("History", 1),
+        ("Home", 0),
+        ("Playlists", 0),
+        ("Search", 0),
+        ("Stats", 1),
+        ("Subscriptions", 10),
+    ];
 
     thread::spawn(move || {
         //thread for calculating tics, moves tx to keep it in scope
@@ -79,6 +203,10 @@ pub async fn tui_render() -> Result<()> {
 
             if event::poll(timeout).expect("poll works") {
                 if let CEvent::Key(key) = event::read().expect("can read events") {
+                    //the radio blocks the event loop while it plays, so it is told to stop from here
+                    if key.code == KeyCode::Char('R') {
+                        utilities::stop_radio();
+                    }
                     tx_input.send(Event::Input(key)).expect("can send events");
                 }
             }
@@ -93,6 +221,9 @@ pub async fn tui_render() -> Result<()> {
     let rt = Runtime::new()?;
     //assumes auth token is expired on startup
     let mut authenticated = false; 
+    //set while a background token refresh is running so only one is started
+    let refresh_running = Arc::new(AtomicBool::new(false));
+    let mut last_refresh_attempt: Option<Instant> = None;
 
     loop {
         if state.active_menu_item == MenuItem::Account && !authenticated {
@@ -113,23 +244,128 @@ pub async fn tui_render() -> Result<()> {
                 }
             });
         }
+        //fetching playlists in the background so long lists don't freeze the tui
+        if state.playlists_requested {
+            state.playlists_requested = false;
+            state.playlists_loading = true;
+            state.playlists_loaded = false;
+            //local playlists are read right away and come first, they don't need youtube at all
+            match local_playlists::load_local_playlists() {
+                Ok(local) => state.playlists.extend(local.iter().map(LocalPlaylist::to_playlist)),
+                Err(e) => state.status = format!("Failed to load local playlists: {}", e),
+            }
+            let generation = state.playlists_generation;
+            let max_pages = state.config.max_pages;
+            let tx_page = tx.clone();
+            //the force refresh key skips the cache for this fetch only
+            let youtube = if state.playlists_force_refresh {
+                state.youtube.force_refresh()
+            } else {
+                state.youtube.clone()
+            };
+            state.playlists_force_refresh = false;
+            rt.spawn(async move {
+                //an api key can't see the user's playlists, the local ones are all there is
+                if youtube.access() != Access::Account {
+                    let _ = tx_page.send(Event::Status("Sign in with 'a' to see your YouTube playlists, local playlists work without it.".to_string()));
+                    let _ = tx_page.send(Event::PlaylistsDone(generation, false));
+                    return;
+                }
+                //the playlists youtube keeps for the account come first, the total only counts the user's own
+                match youtube.system_playlists().await {
+                    Ok(system) => {
+                        let _ = tx_page.send(Event::PlaylistsPage(generation, system, 0));
+                    }
+                    Err(e) => {
+                        let _ = tx_page.send(Event::Status(format!("Failed to fetch the liked videos playlist: {}", errors::describe(&e))));
+                    }
+                }
+                let result = youtube.list_playlists(max_pages, |page, total| {
+                    let _ = tx_page.send(Event::PlaylistsPage(generation, page.to_vec(), total));
+                })
+                .await;
+                if let Err(e) = &result {
+                    let _ = tx_page.send(Event::Status(format!("Failed to fetch playlists: {}", errors::describe(e))));
+                }
+                let _ = tx_page.send(Event::PlaylistsDone(generation, result.is_ok()));
+            });
+        }
+        //fetching the subscriptions and then the newest uploads of every subscribed channel
+        if state.subscriptions_requested {
+            state.subscriptions_requested = false;
+            state.subscriptions_loading = true;
+            let generation = state.subscriptions_generation;
+            let max_pages = state.config.max_pages;
+            let per_channel = state.config.feed_videos_per_channel;
+            let tx_feed = tx.clone();
+            let youtube = if state.subscriptions_force_refresh {
+                state.youtube.force_refresh()
+            } else {
+                state.youtube.clone()
+            };
+            state.subscriptions_force_refresh = false;
+            rt.spawn(async move {
+                if youtube.access() != Access::Account {
+                    let _ = tx_feed.send(Event::Status("Sign in with 'a' to see your subscriptions and their newest uploads.".to_string()));
+                    let _ = tx_feed.send(Event::Feed(generation, vec![]));
+                    return;
+                }
+                let subscriptions = match youtube.list_subscriptions(max_pages).await {
+                    Ok(subscriptions) => subscriptions,
+                    Err(e) => {
+                        let _ = tx_feed.send(Event::Status(format!("Failed to fetch subscriptions: {}", errors::describe(&e))));
+                        let _ = tx_feed.send(Event::Feed(generation, vec![]));
+                        return;
+                    }
+                };
+                let channel_ids: Vec<String> = subscriptions.iter().map(|sub| sub.channel_id.clone()).collect();
+                let _ = tx_feed.send(Event::Subscriptions(generation, subscriptions));
+                let feed = match youtube.latest_uploads(&channel_ids, per_channel).await {
+                    Ok(feed) => feed,
+                    Err(e) => {
+                        let _ = tx_feed.send(Event::Status(format!("Failed to fetch the latest uploads: {}", errors::describe(&e))));
+                        vec![]
+                    }
+                };
+                let _ = tx_feed.send(Event::Feed(generation, feed));
+            });
+        }
+        //refreshing the token in the background a few minutes before it expires
+        //a failed refresh is only tried again after a minute so it doesn't run on every tick
+        let refresh_due = last_refresh_attempt.is_none_or(|at| at.elapsed() >= Duration::from_secs(60));
+        if refresh_due && app_state::token_needs_refresh() && !refresh_running.swap(true, Ordering::SeqCst) {
+            last_refresh_attempt = Some(Instant::now());
+            let tx_refresh = tx.clone();
+            let refresh_running = refresh_running.clone();
+            rt.spawn(async move {
+                if let Err(e) = app_state::refresh_and_set_token().await {
+                    let _ = tx_refresh.send(Event::Status(format!("Failed to refresh token: {}", e)));
+                }
+                refresh_running.store(false, Ordering::SeqCst);
+            });
+        }
         //drawing terminal begins here
         terminal.draw(|rect| 
             {
             let size = rect.size();
-            //the layout is vertical with 2 parts, one that has a set height of 3 rows and the other takes the lest
+            //the layout is vertical with 3 parts, the menu and the status bar have a set height of 3 rows and the tab takes the rest
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(2)
-                .constraints([Constraint::Length(3), Constraint::Min(2)].as_ref())
+                .constraints([Constraint::Length(3), Constraint::Min(2), Constraint::Length(3)].as_ref())
                 .split(size);
             //adding menu items, with colors and modifiers    
             let menu = menu_titles
                 .iter()
-                .map(|t| 
+                .map(|(t, key)| 
                     {
-                    let (first, rest) = t.split_at(1);
+                    let (before, rest) = t.split_at(*key);
+                    let (first, rest) = rest.split_at(1);
                     Spans::from(vec![
+                        Span::styled(
+                            before,
+                            Style::default().fg(state.selected_theme.other_menu_items.0),
+                        ),
                         Span::styled(
                             first,
                             Style::default()
@@ -151,6 +387,18 @@ pub async fn tui_render() -> Result<()> {
                 .divider(Span::raw("|"));
 
             rect.render_widget(tabs, chunks[0]);
+            let quota_limits = state.config.quota_limits();
+            rect.render_widget(
+                render::render_status(
+                    &state.selected_theme,
+                    state.retry_status.as_deref().unwrap_or(&state.status),
+                    state.youtube.quota_remaining(),
+                    quota_limits,
+                    state.autoplay,
+                    state.youtube.access(),
+                ),
+                chunks[2],
+            );
             //match case for the currently active menu item    
             match state.active_menu_item 
             {
@@ -168,15 +416,35 @@ pub async fn tui_render() -> Result<()> {
                 }
                 MenuItem::Playlists => 
                 {
-                    rect.render_widget(
-                        render::render_playlists(
-                            &state.selected_theme,
-                            &state.playlists,
-                            state.playlist_selection_mode,
-                            &state.playlist_number_input,
+                    match &state.playlist_open 
+                    {
+                        Some(playlist) => rect.render_widget(
+                            render::render_playlist_items(
+                                &state.selected_theme,
+                                playlist,
+                                &state.playlist_items,
+                                state.playlist_item_cursor,
+                                &state.ratings,
+                                state.local_video_input.as_deref(),
+                                chunks[1].height.saturating_sub(2),
+                            ),
+                            chunks[1],
                         ),
-                        chunks[1],
-                    );
+                        None => rect.render_widget(
+                            render::render_playlists(
+                                &state.selected_theme,
+                                &state.playlists,
+                                state.playlists_total,
+                                state.playlists_loading,
+                                if state.playlist_selection_mode { Some(state.playlist_number_input.as_str()) } else { None },
+                                state.playlist_form.as_ref(),
+                                &state.config.save_for_later_playlist,
+                                state.playlist_cursor,
+                                chunks[1].height.saturating_sub(2),
+                            ),
+                            chunks[1],
+                        ),
+                    }
                 }
 
                 MenuItem::Account => 
@@ -188,39 +456,279 @@ pub async fn tui_render() -> Result<()> {
                 }
                 MenuItem::Search => 
                 {
+                    //the prompt with the active filters on top, results or the filter panel below
+                    let search_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(5), Constraint::Min(2)].as_ref())
+                        .split(chunks[1]);
                     rect.render_widget(
-                        render::render_search_prompt(&state.search_input),
-                        chunks[1],
+                        render::render_search_prompt(&state.search_input, &state.search_filters),
+                        search_chunks[0],
                     );
+                    if state.search_filters_open {
+                        rect.render_widget(
+                            render::render_search_filters(
+                                &state.selected_theme,
+                                &state.search_filters,
+                                state.search_filter_cursor,
+                                state.search_filter_editing,
+                                &state.search_filter_input,
+                            ),
+                            search_chunks[1],
+                        );
+                    } else {
+                        rect.render_widget(
+                            render::render_search(
+                                &state.selected_theme,
+                                &state.search_result,
+                                state.search_attempted,
+                                state.search_next_page.is_some(),
+                                &state.ratings,
+                                &state.subscribed,
+                                if state.search_selection_mode {
+                                    Some((state.selection_action, state.search_number_input.as_str()))
+                                } else {
+                                    None
+                                },
+                            ),
+                            search_chunks[1],
+                        );
+                    }
+                }
+                MenuItem::History => 
+                {
                     rect.render_widget(
-                        render::render_search(
+                        render::render_history(
                             &state.selected_theme,
-                            &state.search_result,
-                            state.search_attempted,
-                            state.search_selection_mode,
-                            &state.search_number_input,
+                            &state.history,
+                            &state.history_filter_input,
+                            state.history_typing,
+                            if state.history_selection_mode {
+                                Some((state.selection_action, state.history_number_input.as_str()))
+                            } else {
+                                None
+                            },
                         ),
                         chunks[1],
                     );
                 }
+                MenuItem::Stats => 
+                {
+                    let stats = stats::compute_stats(
+                        &state.history,
+                        state.stats_range,
+                        state.stats_grouping,
+                        chrono::Local::now(),
+                    );
+                    //header, bar chart, sparkline and the two top lists next to each other
+                    let stats_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(
+                            [
+                                Constraint::Length(3),
+                                Constraint::Percentage(45),
+                                Constraint::Length(5),
+                                Constraint::Min(4),
+                            ]
+                            .as_ref(),
+                        )
+                        .split(chunks[1]);
+                    let top_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                        .split(stats_chunks[3]);
+
+                    //only the most recent bars and days fit on screen, each bar is 5 wide with a gap of 1
+                    let max_bars = (stats_chunks[1].width.saturating_sub(2) / 6) as usize;
+                    let bars: Vec<(&str, u64)> = stats.buckets
+                        [stats.buckets.len().saturating_sub(max_bars)..]
+                        .iter()
+                        .map(|(label, minutes)| (label.as_str(), *minutes))
+                        .collect();
+                    let max_days = stats_chunks[2].width.saturating_sub(2) as usize;
+                    let days = &stats.daily_minutes[stats.daily_minutes.len().saturating_sub(max_days)..];
+
+                    rect.render_widget(
+                        render::render_stats_summary(&state.selected_theme, &stats, state.stats_range, state.stats_grouping),
+                        stats_chunks[0],
+                    );
+                    rect.render_widget(
+                        render::render_stats_chart(&state.selected_theme, &bars, state.stats_grouping),
+                        stats_chunks[1],
+                    );
+                    rect.render_widget(
+                        render::render_stats_sparkline(&state.selected_theme, days),
+                        stats_chunks[2],
+                    );
+                    rect.render_widget(
+                        render::render_stats_top_channels(&state.selected_theme, &stats.top_channels),
+                        top_chunks[0],
+                    );
+                    rect.render_widget(
+                        render::render_stats_top_tracks(&state.selected_theme, &stats.top_tracks),
+                        top_chunks[1],
+                    );
+                }
+                MenuItem::Subscriptions => 
+                {
+                    //the channels on the left, their newest uploads on the right
+                    let subscription_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+                        .split(chunks[1]);
+                    rect.render_widget(
+                        render::render_subscriptions(
+                            &state.selected_theme,
+                            &state.subscriptions,
+                            state.subscriptions_loading,
+                            if state.subscription_selection_mode { Some(state.subscription_number_input.as_str()) } else { None },
+                        ),
+                        subscription_chunks[0],
+                    );
+                    rect.render_widget(
+                        render::render_feed(
+                            &state.selected_theme,
+                            &state.feed,
+                            &state.seen,
+                            state.feed_cursor,
+                            state.subscriptions_loading,
+                            subscription_chunks[1].height.saturating_sub(2),
+                        ),
+                        subscription_chunks[1],
+                    );
+                }
                 MenuItem::Commands => 
                 {
                     rect.render_widget(render::render_commands(&state.selected_theme), chunks[1]);
                 }
             }
+            //the channel page covers whatever tab it was opened from
+            if let Some(channel) = &state.channel_open 
+            {
+                let channel_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(6), Constraint::Min(0)].as_ref())
+                    .split(chunks[1]);
+                let list_chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+                    .split(channel_chunks[1]);
+                rect.render_widget(Clear, chunks[1]);
+                rect.render_widget(render::render_channel_header(
+                        &state.selected_theme,
+                        channel,
+                        matches!(state.subscribed.get(&channel.id), Some(Some(_))),
+                    ), channel_chunks[0]);
+                rect.render_widget(
+                    render::render_channel_uploads(
+                        &state.selected_theme,
+                        &state.channel_uploads,
+                        state.channel_upload_cursor,
+                        !state.channel_playlists_focused,
+                        list_chunks[0].height.saturating_sub(2),
+                    ),
+                    list_chunks[0],
+                );
+                rect.render_widget(
+                    render::render_channel_playlists(
+                        &state.selected_theme,
+                        &state.channel_playlists,
+                        state.channel_playlist_cursor,
+                        state.channel_playlists_focused,
+                        list_chunks[1].height.saturating_sub(2),
+                    ),
+                    list_chunks[1],
+                );
+            }
+            //the details pane goes over the channel page as well
+            if let Some(details) = &state.details_open 
+            {
+                rect.render_widget(Clear, chunks[1]);
+                rect.render_widget(
+                    render::render_video_details(&state.selected_theme, details, &state.details_input, state.details_scroll),
+                    chunks[1],
+                );
+            }
+            //and the comments go over the details pane they were opened from
+            if let Some(comments) = &state.comments 
+            {
+                rect.render_widget(Clear, chunks[1]);
+                rect.render_widget(
+                    render::render_comments(
+                        &state.selected_theme,
+                        comments,
+                        chunks[1].width.saturating_sub(2),
+                        chunks[1].height.saturating_sub(2),
+                    ),
+                    chunks[1],
+                );
+            }
+            //picking the playlist for a video covers whatever tab it was started from
+            if let Some(pending) = &state.add_to_playlist 
+            {
+                rect.render_widget(Clear, chunks[1]);
+                rect.render_widget(
+                    render::render_add_to_playlist(
+                        &state.selected_theme,
+                        pending,
+                        &state.playlists,
+                        state.playlists_loading,
+                    ),
+                    chunks[1],
+                );
+            }
         })?;
         //handling events
         match rx.recv()? 
         {   //input event handler from the app_state
             Event::Input(event) => 
             {
-                if app_state::event_handler(Event::Input(event), &mut state, &mut terminal).await? 
+                handling_input.store(true, Ordering::SeqCst);
+                let quit = app_state::event_handler(Event::Input(event), &mut state, &mut terminal).await;
+                handling_input.store(false, Ordering::SeqCst);
+                if quit? 
                 {
                     break Ok(());
                 }
+                //the terminal doesn't know about the text written over the status bar
+                if status_drawn.swap(false, Ordering::SeqCst) 
+                {
+                    terminal.clear()?;
+                }
             }
             Event::Tick => {}
             Event::Message(msg) => state.messages.push(msg),
+            Event::Status(msg) => state.status = msg,
+            Event::RetryStatus(retry_status) => state.retry_status = retry_status,
+            //pages from an older request are dropped
+            Event::PlaylistsPage(generation, playlists, total) => {
+                if generation == state.playlists_generation {
+                    state.playlists.extend(playlists);
+                    state.playlists_total = total;
+                }
+            }
+            Event::PlaylistsDone(generation, complete) => {
+                if generation == state.playlists_generation {
+                    state.playlists_loading = false;
+                    state.playlists_loaded = complete;
+                }
+            }
+            Event::Subscriptions(generation, subscriptions) => {
+                if generation == state.subscriptions_generation {
+                    //the full list also tells which channels the user is subscribed to
+                    for subscription in &subscriptions {
+                        state.subscribed.insert(subscription.channel_id.clone(), Some(subscription.id.clone()));
+                    }
+                    state.subscriptions = subscriptions;
+                }
+            }
+            Event::Feed(generation, feed) => {
+                if generation == state.subscriptions_generation {
+                    state.feed_cursor = state.feed_cursor.min(feed.len().saturating_sub(1));
+                    state.feed = feed;
+                    state.subscriptions_loading = false;
+                }
+            }
         }
     }
 }
\ No newline at end of file
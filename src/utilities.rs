@@ -1,74 +1,17 @@
+use std::collections::HashSet;
 use std::fs;
 use std::io;
 use std::path::Path;
 
 use anyhow::anyhow;
-use anyhow::{Context, Result};
-use reqwest::Client;
-use reqwest::Url;
-use serde::Deserialize;
-use std::fs::File;
-use std::io::Write;
+use anyhow::Result;
+use chrono::Local;
 use std::process::Command;
-#[derive(Debug, Deserialize)]
-pub struct PlaylistListResponse {
-    pub items: Vec<PlaylistItem>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct PlaylistItem {
-    pub id: String,
-    pub snippet: Snippet,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct Snippet {
-    pub title: String,
-    pub resourceId: Option<ResourceId>,
-}
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Deserialize)]
-pub struct ResourceId {
-    pub videoId: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct SearchListResponse {
-    items: Vec<SearchItem>,
-}
-
-#[derive(Debug, Deserialize)]
-struct SearchItem {
-    id: SearchItemId,
-}
-
-#[derive(Debug, Deserialize)]
-struct SearchItemId {
-    videoId: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct VideoListResponse {
-    items: Vec<VideoDetailItem>,
-}
-
-#[derive(Debug, Deserialize)]
-struct VideoDetailItem {
-    snippet: VideoSnippet,
-    contentDetails: ContentDetails,
-    id: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct VideoSnippet {
-    title: String,
-    channelTitle: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ContentDetails {
-    duration: String, // ISO 8601
-}
+use crate::history::{self, HistoryEntry, PlaySource};
+use crate::youtube::{PlaylistEntry, YouTubeClient};
 
 /// direct audio stream from yt, using yt-dlp
 pub fn get_audio_url(video_url: &str) -> Result<String> {
@@ -93,240 +36,175 @@ pub fn get_audio_url(video_url: &str) -> Result<String> {
     Ok(last_line.to_string())
 }
 
-/// plays a single song using mpv
-pub fn play_song(link: &str) {
-    match get_audio_url(link) {
-        Ok(audio_url) => {
-            let status = Command::new("mpv")
-                .args([
-                    "--no-video",
-                    "--really-quiet",
-                    "--no-config",
-                    "--idle=no",
-                    &audio_url,
-                ])
-                .status();
-
-            match status {
-                Ok(status) if status.success() => {}
-                Ok(status) => eprintln!("Mpv exited with status: {:?}", status.code()),
-                Err(e) => eprintln!("Failed to start mpv: {}", e),
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to get audio stream: {}", e);
-        }
+/// plays a single song using mpv, starting start_secs into it, returns how long mpv played
+pub fn play_song(link: &str, start_secs: u64) -> Result<Duration> {
+    let audio_url = get_audio_url(link).map_err(|e| anyhow!("Failed to get audio stream: {}", e))?;
+    let started = Instant::now();
+    let status = Command::new("mpv")
+        .args([
+            "--no-video",
+            "--really-quiet",
+            "--no-config",
+            "--idle=no",
+            &format!("--start={}", start_secs),
+            &audio_url,
+        ])
+        .status()
+        .map_err(|e| anyhow!("Failed to start mpv: {}", e))?;
+
+    if !status.success() {
+        return Err(anyhow!("Mpv exited with status: {:?}", status.code()));
     }
+    Ok(started.elapsed())
 }
 
-pub fn play_song_by_id(video_id: &str) {
-    let link = format!("https://www.youtube.com/watch?v={}", video_id);
-
-    match get_audio_url(&link) {
-        Ok(audio_url) => {
-            let status = Command::new("mpv")
-                .args([
-                    "--no-video",
-                    "--really-quiet",
-                    "--no-config",
-                    "--idle=no",
-                    &audio_url,
-                ])
-                .status();
-
-            match status {
-                Ok(status) if status.success() => {}
-                Ok(status) => eprintln!("Mpv exited with status: {:?}", status.code()),
-                Err(e) => eprintln!("Failed to start mpv: {}", e),
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to get audio stream: {}", e);
-        }
-    }
+/// plays a video by its id and records the play in the local history
+pub fn play_song_by_id(video_id: &str, title: &str, uploader: &str, source: PlaySource) -> Result<()> {
+    play_song_by_id_from(video_id, title, uploader, source, 0)
 }
 
-pub async fn list_playlists(access_token: &str) -> Result<Vec<(String, String)>> {
-    let url =
-        "https://www.googleapis.com/youtube/v3/playlists?part=snippet&mine=true&maxResults=50";
-
-    let client = reqwest::Client::new(); // new client
-    let response = client
-        .get(url) //url that we request
-        .bearer_auth(access_token) //oauth token with the bearer schema
-        .send() //sending the request
-        .await //waiting for response
-        .context("Failed to send playlist request")?; //context if something goes wrong
-
-    let status = response.status(); //status code for request reponse
-    let text = response
-        .text()
-        .await
-        .context("Failed to read response text")?;
-    // always write raw response, even if it's an error
-
-    if !status.is_success() {
-        return Err(anyhow!("YouTube API error ({}): {}", status.as_u16(), text));
-    }
-
-    let playlists_response: PlaylistListResponse =
-        serde_json::from_str(&text).context("Failed to parse playlists JSON")?;
-
-    let playlists = playlists_response
-        .items
-        .into_iter()
-        .map(|item| (item.snippet.title, item.id))
-        .collect();
-
-    Ok(playlists)
+/// the same, but starting start_secs into the video, for the timestamps of a description
+/// only a play that worked goes in the history
+pub fn play_song_by_id_from(video_id: &str, title: &str, uploader: &str, source: PlaySource, start_secs: u64) -> Result<()> {
+    let link = format!("https://www.youtube.com/watch?v={}", video_id);
+    let started_at = Local::now();
+    let listened = play_song(&link, start_secs)?;
+
+    let entry = HistoryEntry {
+        video_id: video_id.to_string(),
+        title: title.to_string(),
+        uploader: uploader.to_string(),
+        started_at,
+        listened_secs: listened.as_secs(),
+        source,
+    };
+    history::append_entry(&entry).map_err(|e| anyhow!("Failed to write history: {}", e))
+}
+
+/// one track of a youtube mix
+pub struct RadioTrack {
+    pub video_id: String,
+    pub title: String,
+    pub uploader: String,
 }
 
-pub async fn get_videos_from_playlist(
-    access_token: &str,
-    playlist_id: &str,
-) -> Result<Vec<(String, String)>> {
-    let url = format!(
-        "https://www.googleapis.com/youtube/v3/playlistItems?part=snippet&playlistId={}&maxResults=50",
-        playlist_id
-    );
-
-    let client = Client::new();
-    let response = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .context("Failed to send playlistItems request")?;
-
-    let status = response.status();
-    let text = response
-        .text()
-        .await
-        .context("Failed to read response text")?;
+/// the mix youtube builds around a video (the RD<videoId> playlist), read with yt-dlp, it starts with the video itself
+pub fn related_tracks(video_id: &str) -> Result<Vec<RadioTrack>> {
+    let mix_url = format!("https://www.youtube.com/watch?v={}&list=RD{}", video_id, video_id);
+    let output = Command::new("yt-dlp")
+        .args([
+            "--flat-playlist",
+            "--playlist-end",
+            "25",
+            "--print",
+            "%(id)s\t%(title)s\t%(uploader)s",
+            &mix_url,
+        ])
+        .output()
+        .map_err(|e| anyhow!("Failed to run yt-dlp: {}", e))?;
 
-    if !status.is_success() {
-        return Err(anyhow::anyhow!(
-            "YouTube API error ({}): {}",
-            status.as_u16(),
-            text
+    if !output.status.success() {
+        return Err(anyhow!(
+            "yt-dlp failed: {}",
+            String::from_utf8_lossy(&output.stderr)
         ));
     }
 
-    let playlist_items: PlaylistListResponse =
-        serde_json::from_str(&text).context("Failed to parse playlistItems JSON")?;
-
-    let video_urls = playlist_items
-        .items
-        .into_iter()
-        .filter_map(|item| {
-            if let Some(resource) = item.snippet.resourceId {
-                let url = format!("https://www.youtube.com/watch?v={}", resource.videoId);
-                Some((item.snippet.title, url))
-            } else {
-                None
-            }
+    let tracks = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let video_id = fields.next()?.to_string();
+            let title = fields.next().unwrap_or_default().to_string();
+            //yt-dlp prints NA for fields a flat playlist doesn't have
+            let uploader = fields.next().filter(|uploader| *uploader != "NA").unwrap_or_default().to_string();
+            Some(RadioTrack { video_id, title, uploader })
         })
         .collect();
-
-    Ok(video_urls)
+    Ok(tracks)
+}
+
+//how many of the latest plays the radio skips, so it doesn't repeat what was just heard
+const RECENT_PLAYS: usize = 50;
+
+//set from the input thread when R is pressed, the radio checks it between tracks
+static RADIO_STOP: AtomicBool = AtomicBool::new(false);
+
+/// asks a playing radio to stop once the current track ends
+pub fn stop_radio() {
+    RADIO_STOP.store(true, Ordering::SeqCst);
+}
+
+/// keeps playing related tracks after a video, at most max_tracks of them, until R is pressed
+/// every track is picked from the mix of the one before it, skipping the recent plays and the tracks of this radio
+/// returns why the radio ended, for the status bar
+pub fn play_radio(video_id: &str, max_tracks: usize) -> Result<&'static str> {
+    //an R pressed before the radio started isn't meant for it
+    RADIO_STOP.store(false, Ordering::SeqCst);
+    let mut played: HashSet<String> = history::load_history()
+        .map(|entries| entries.into_iter().take(RECENT_PLAYS).map(|entry| entry.video_id).collect())
+        .unwrap_or_default();
+    played.insert(video_id.to_string());
+
+    let mut seed = video_id.to_string();
+    for _ in 0..max_tracks {
+        let next = related_tracks(&seed)
+            .map_err(|e| anyhow!("Failed to get related tracks: {}", e))?
+            .into_iter()
+            .find(|track| !played.contains(&track.video_id));
+        let track = match next {
+            Some(track) => track,
+            None => return Ok("No related tracks left that you haven't heard."),
+        };
+        if RADIO_STOP.swap(false, Ordering::SeqCst) {
+            return Ok("Radio stopped.");
+        }
+        play_song_by_id(&track.video_id, &track.title, &track.uploader, PlaySource::Radio)?;
+        played.insert(track.video_id.clone());
+        seed = track.video_id;
+    }
+    Ok("Radio finished.")
 }
 
-pub async fn play_playlist(access_token: &str, playlist_id: &str) -> Result<()> {
-    let videos = get_videos_from_playlist(access_token, playlist_id).await?;
+//plays the playlist page by page, so playback starts before a long playlist is fully fetched
+pub async fn play_playlist(
+    youtube: &YouTubeClient,
+    playlist_id: &str,
+    max_pages: usize,
+) -> Result<()> {
+    let mut failed = Vec::new();
+    let videos = youtube
+        .get_videos_from_playlist(playlist_id, max_pages, |page, _total| {
+            if let Err(e) = play_entries(page, PlaySource::Playlist) {
+                failed.push(e);
+            }
+        })
+        .await?;
 
     if videos.is_empty() {
-        println!("📭 No videos found in the playlist.");
-        return Ok(());
+        return Err(anyhow!("No videos found in the playlist."));
     }
-
-    for (title, url) in videos {
-        println!(" Now playing: {}", title);
-        play_song(&url);
+    match failed.pop() {
+        Some(e) => Err(e),
+        None => Ok(()),
     }
-
-    Ok(())
 }
 
-pub async fn search_videos(
-    access_token: &str,
-    query: &str,
-) -> Result<Vec<(String, String, String, String)>> {
-    let client = Client::new();
-    let mut url = Url::parse("https://www.googleapis.com/youtube/v3/search").unwrap();
-    url.query_pairs_mut()
-        .append_pair("part", "id")
-        .append_pair("type", "video")
-        .append_pair("q", query)
-        .append_pair("maxResults", "15");
-
-    let search_resp = client
-        .get(&url.to_string())
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .context("Failed to send search request")?;
-
-    let status = search_resp.status();
-    let body = search_resp.text().await?;
-
-    if !status.is_success() {
-        return Err(anyhow::anyhow!("Search failed ({}): {}", status, body));
-    }
-
-    let search_data: SearchListResponse =
-        serde_json::from_str(&body).context("Failed to parse search response")?;
-
-    let video_ids: Vec<String> = search_data
-        .items
-        .into_iter()
-        .map(|item| item.id.videoId)
-        .collect();
-
-    if video_ids.is_empty() {
-        return Ok(vec![]);
+//plays the videos one after the other, the queue of youtube and local playlists and of a channel's uploads
+//a video that can't be played is skipped, the last failure is returned once the queue is done
+pub fn play_entries(entries: &[PlaylistEntry], source: PlaySource) -> Result<()> {
+    let mut failed = 0;
+    let mut last_error = None;
+    for entry in entries {
+        if let Err(e) = play_song_by_id(&entry.video_id, &entry.title, &entry.uploader, source) {
+            failed += 1;
+            last_error = Some(e);
+        }
     }
-
-    // Step 2: Fetch video details
-    let video_url = format!(
-        "https://www.googleapis.com/youtube/v3/videos?part=snippet,contentDetails&id={}",
-        video_ids.join(",")
-    );
-
-    let video_resp = client
-        .get(&video_url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .context("Failed to send video details request")?;
-
-    let status = video_resp.status();
-    let body = video_resp.text().await?;
-
-    if !status.is_success() {
-        return Err(anyhow::anyhow!(
-            "Video details failed ({}): {}",
-            status,
-            body
-        ));
+    match last_error {
+        Some(e) => Err(anyhow!("{} of {} videos couldn't be played: {}", failed, entries.len(), e)),
+        None => Ok(()),
     }
-
-    let details: VideoListResponse =
-        serde_json::from_str(&body).context("Failed to parse video details response")?;
-
-    let results = details
-        .items
-        .into_iter()
-        .map(|item| {
-            let title = item.snippet.title;
-            let uploader = item.snippet.channelTitle;
-            let duration = item.contentDetails.duration;
-            let id = item.id;
-
-            (title, duration, uploader, id)
-        })
-        .collect();
-
-    Ok(results)
 }
 
 pub fn get_theme_files() -> io::Result<Vec<String>> {
@@ -0,0 +1,1707 @@
+//youtube is the client for the YouTube Data API
+//every request goes through one YouTubeClient, which shares a single http client
+//and builds urls from a base url so it can be pointed at a local mock server
+use crate::authenticate::OAuthToken;
+use crate::cache::{self, CacheEntry};
+use crate::config::Config;
+use crate::errors::ApiError;
+use crate::quota::{QuotaLimits, QuotaTracker, COST_READ, COST_SEARCH, COST_WRITE};
+use crate::retry::RetryPolicy;
+use crate::search_filters::SearchFilters;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::stream::{self, StreamExt};
+use chrono::{DateTime, Duration, Utc};
+use oauth2::TokenResponse;
+use reqwest::{Method, Url};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub const DEFAULT_BASE_URL: &str = "https://www.googleapis.com/youtube/v3";
+
+//a playlist of the user
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    //"private", "unlisted" or "public", "local" for local playlists
+    pub privacy: String,
+    //playlists youtube keeps for every account, like the liked videos, these can't be edited
+    pub system: bool,
+    //kept in local_playlists.json instead of on youtube
+    pub local: bool,
+}
+
+//a video with the details shown in search results, duration is in ISO 8601
+#[derive(Debug, Clone)]
+pub struct Video {
+    pub id: String,
+    pub title: String,
+    pub uploader: String,
+    pub channel_id: String,
+    pub duration: String,
+}
+
+//everything the details pane shows about one video, counts are None when the uploader hides them
+#[derive(Debug, Clone)]
+pub struct VideoDetails {
+    pub id: String,
+    pub title: String,
+    pub uploader: String,
+    pub description: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub duration: String,
+    pub view_count: Option<u64>,
+    pub like_count: Option<u64>,
+    pub comment_count: Option<u64>,
+    pub tags: Vec<String>,
+    //the name of the category, or its id if the name couldn't be looked up
+    pub category: String,
+}
+
+//a top level comment or a reply, the text is plain text
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub author: String,
+    pub text: String,
+    pub like_count: u64,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+//a top level comment with its replies, youtube only sends the first few replies with it
+#[derive(Debug, Clone)]
+pub struct CommentThread {
+    //the id of the top level comment, the rest of the replies are asked for with it
+    pub id: String,
+    pub comment: Comment,
+    pub reply_count: u32,
+    pub replies: Vec<Comment>,
+}
+
+//one page of comment threads, the token asks for the page after it
+#[derive(Debug, Clone)]
+pub struct CommentPage {
+    pub threads: Vec<CommentThread>,
+    pub next_page_token: Option<String>,
+}
+
+//how comment threads are sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentOrder {
+    Relevance,
+    Time,
+}
+
+impl CommentOrder {
+    fn api_value(self) -> &'static str {
+        match self {
+            CommentOrder::Relevance => "relevance",
+            CommentOrder::Time => "time",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CommentOrder::Relevance => "top comments",
+            CommentOrder::Time => "newest first",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            CommentOrder::Relevance => CommentOrder::Time,
+            CommentOrder::Time => CommentOrder::Relevance,
+        }
+    }
+}
+
+//a single search result, searches can return playlists and channels next to videos
+#[derive(Debug, Clone)]
+pub enum SearchResult {
+    Video(Video),
+    Playlist { id: String, title: String, uploader: String, channel_id: String },
+    Channel { id: String, title: String },
+}
+
+impl SearchResult {
+    //the id and name of the channel behind the result, a channel result is its own channel
+    pub fn channel(&self) -> (&str, &str) {
+        match self {
+            SearchResult::Video(video) => (&video.channel_id, &video.uploader),
+            SearchResult::Playlist { channel_id, uploader, .. } => (channel_id, uploader),
+            SearchResult::Channel { id, title } => (id, title),
+        }
+    }
+}
+
+//one page of search results, the token asks for the page after it
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub next_page_token: Option<String>,
+}
+
+//a channel as shown on its own page, subscriber_count is None if the channel hides it
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub subscriber_count: Option<u64>,
+    //the hidden playlist every channel keeps its uploads in
+    pub uploads: String,
+}
+
+//a channel the user is subscribed to
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    //the id of the subscription itself, unsubscribing needs it
+    pub id: String,
+    pub channel_id: String,
+    pub title: String,
+}
+
+//an upload of a subscribed channel, for the latest uploads feed
+#[derive(Debug, Clone)]
+pub struct FeedVideo {
+    pub video_id: String,
+    pub title: String,
+    pub channel_title: String,
+    pub published_at: DateTime<Utc>,
+}
+
+//the rating the user gave a video
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Like,
+    Dislike,
+    Unrated,
+}
+
+impl Rating {
+    fn api_value(self) -> &'static str {
+        match self {
+            Rating::Like => "like",
+            Rating::Dislike => "dislike",
+            Rating::Unrated => "none",
+        }
+    }
+
+    fn from_api(value: &str) -> Self {
+        match value {
+            "like" => Rating::Like,
+            "dislike" => Rating::Dislike,
+            _ => Rating::Unrated,
+        }
+    }
+
+    //shown next to rated videos, unrated ones show nothing
+    pub fn label(self) -> &'static str {
+        match self {
+            Rating::Like => " (liked)",
+            Rating::Dislike => " (disliked)",
+            Rating::Unrated => "",
+        }
+    }
+}
+
+//a video inside a playlist
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    //the id of the playlist item, removing or moving the video needs it
+    pub item_id: String,
+    pub video_id: String,
+    pub title: String,
+    pub uploader: String,
+    //the channel that uploaded the video, empty for videos youtube doesn't say it for
+    pub channel_id: String,
+    //zero based place in the playlist
+    pub position: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistListResponse {
+    items: Vec<PlaylistItem>,
+    next_page_token: Option<String>,
+    page_info: Option<PageInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    total_results: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItem {
+    id: String,
+    snippet: Snippet,
+    //only asked for when listing playlists
+    status: Option<Status>,
+    //only asked for when building the uploads feed
+    content_details: Option<ItemContentDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemContentDetails {
+    video_published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Status {
+    privacy_status: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snippet {
+    title: String,
+    #[serde(default)]
+    description: String,
+    //only set for playlist items
+    #[serde(default)]
+    position: u32,
+    //when the item was added, for an uploads playlist that is about when it was published
+    published_at: Option<DateTime<Utc>>,
+    video_owner_channel_title: Option<String>,
+    video_owner_channel_id: Option<String>,
+    resource_id: Option<ResourceId>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceId {
+    video_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchListResponse {
+    items: Vec<SearchItem>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchItem {
+    id: SearchItemId,
+    snippet: SearchSnippet,
+}
+
+//only the id matching the kind is set, e.g. "youtube#playlist" comes with a playlistId
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchItemId {
+    kind: String,
+    video_id: Option<String>,
+    playlist_id: Option<String>,
+    channel_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchSnippet {
+    title: String,
+    #[serde(default)]
+    channel_title: String,
+    #[serde(default)]
+    channel_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelListResponse {
+    #[serde(default)]
+    items: Vec<ChannelItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelItem {
+    content_details: ChannelContentDetails,
+    //only asked for on the channel page
+    snippet: Option<ChannelSnippet>,
+    statistics: Option<ChannelStatistics>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelSnippet {
+    title: String,
+    #[serde(default)]
+    description: String,
+}
+
+//the api sends counts as strings
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelStatistics {
+    subscriber_count: Option<String>,
+    #[serde(default)]
+    hidden_subscriber_count: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionListResponse {
+    items: Vec<SubscriptionItem>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubscriptionItem {
+    id: String,
+    snippet: SubscriptionSnippet,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionSnippet {
+    title: String,
+    resource_id: ChannelResourceId,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelResourceId {
+    channel_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelContentDetails {
+    related_playlists: RelatedPlaylists,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelatedPlaylists {
+    uploads: String,
+    //the rest are only set for the user's own channel, and youtube leaves out the watch ones for most accounts
+    #[serde(default)]
+    likes: String,
+    #[serde(default)]
+    watch_later: String,
+    #[serde(default)]
+    watch_history: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RatingListResponse {
+    #[serde(default)]
+    items: Vec<RatingItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RatingItem {
+    video_id: String,
+    rating: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VideoListResponse {
+    items: Vec<VideoDetailItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoDetailItem {
+    snippet: VideoSnippet,
+    content_details: ContentDetails,
+    id: String,
+    //only asked for by the details pane
+    statistics: Option<VideoStatistics>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoSnippet {
+    title: String,
+    channel_title: String,
+    #[serde(default)]
+    channel_id: String,
+    #[serde(default)]
+    description: String,
+    published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    category_id: String,
+}
+
+//the api sends counts as strings, hidden ones are left out
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoStatistics {
+    view_count: Option<String>,
+    like_count: Option<String>,
+    comment_count: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentThreadListResponse {
+    #[serde(default)]
+    items: Vec<CommentThreadItem>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentThreadItem {
+    snippet: CommentThreadSnippet,
+    replies: Option<CommentReplies>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentThreadSnippet {
+    top_level_comment: CommentItem,
+    #[serde(default)]
+    total_reply_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentReplies {
+    #[serde(default)]
+    comments: Vec<CommentItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentListResponse {
+    #[serde(default)]
+    items: Vec<CommentItem>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentItem {
+    id: String,
+    snippet: CommentSnippet,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentSnippet {
+    #[serde(default)]
+    author_display_name: String,
+    #[serde(default)]
+    text_display: String,
+    #[serde(default)]
+    like_count: u64,
+    published_at: Option<DateTime<Utc>>,
+}
+
+impl From<CommentItem> for Comment {
+    fn from(item: CommentItem) -> Self {
+        Comment {
+            author: item.snippet.author_display_name,
+            text: item.snippet.text_display,
+            like_count: item.snippet.like_count,
+            published_at: item.snippet.published_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoryListResponse {
+    #[serde(default)]
+    items: Vec<CategoryItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoryItem {
+    snippet: CategorySnippet,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategorySnippet {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentDetails {
+    duration: String, // ISO 8601
+}
+
+//what a single request brought back
+enum Fetched {
+    //the cached body is still current, google answered 304
+    NotModified,
+    Body { text: String, etag: Option<String> },
+}
+
+//everything needed to send one api call, reads are GETs without a body
+struct Request<'a> {
+    method: Method,
+    url: &'a Url,
+    body: Option<&'a serde_json::Value>,
+    //the ETag of the cached response, sent as If-None-Match
+    etag: Option<&'a str>,
+    //what is being requested, for error and retry messages
+    what: &'a str,
+    cost: u32,
+    //about the user's own account, an api key isn't enough for these
+    account_only: bool,
+}
+
+//how a request proves who sends it
+enum Auth<'a> {
+    //the oauth access token of the signed in user
+    Token(&'a str),
+    //the api key from the config, only for public data
+    Key(&'a str),
+}
+
+//what the client can reach right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    //signed in, everything works
+    Account,
+    //not signed in, public reads go out with the api key
+    ApiKey,
+    //not signed in and no api key, nothing works until signing in
+    SignedOut,
+}
+
+//where the client gets the oauth token of the signed in user from
+//the tui hands in the token it keeps for the account, tests hand in their own
+pub trait TokenSource: Send + Sync {
+    //the current access token, None while nobody is signed in
+    fn token(&self) -> Option<OAuthToken>;
+    //a new access token, after youtube rejected the current one
+    fn refresh(&self) -> BoxFuture<'_, Result<OAuthToken>>;
+    //names the account in the response cache, so one account never gets another's responses
+    fn account_key(&self) -> String;
+}
+
+//gets a message while a call is waiting to be retried, and None once it is over
+pub type RetryNotifier = Arc<dyn Fn(Option<String>) + Send + Sync>;
+
+//cloning is cheap, the http client is reference counted inside
+#[derive(Clone)]
+pub struct YouTubeClient {
+    http: reqwest::Client,
+    base_url: String,
+    quota_limits: QuotaLimits,
+    //today's spending, shared by the copies handed to background fetches
+    quota: Arc<QuotaTracker>,
+    //where the responses are cached
+    cache_dir: PathBuf,
+    cache_ttl: Duration,
+    retry_policy: RetryPolicy,
+    //how many results a single search request asks for
+    search_page_size: u32,
+    on_retry: RetryNotifier,
+    tokens: Arc<dyn TokenSource>,
+    //skips fresh cache entries and always asks youtube, set with force_refresh()
+    force_refresh: bool,
+    //used for public reads while nobody is signed in
+    api_key: Option<String>,
+}
+
+impl YouTubeClient {
+    //the base url is DEFAULT_BASE_URL unless the config says otherwise, the same goes for the cache folder and the quota file
+    pub fn new<F>(config: &Config, tokens: Arc<dyn TokenSource>, on_retry: F) -> Self
+    where
+        F: Fn(Option<String>) + Send + Sync + 'static,
+    {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: config.api_base_url.trim_end_matches('/').to_string(),
+            quota_limits: config.quota_limits(),
+            quota: Arc::new(QuotaTracker::load(&config.quota_file)),
+            cache_dir: PathBuf::from(&config.cache_dir),
+            cache_ttl: Duration::seconds(config.cache_ttl_secs as i64),
+            retry_policy: config.retry_policy(),
+            //the api allows at most 50 results per page
+            search_page_size: config.search_page_size.clamp(1, 50),
+            on_retry: Arc::new(on_retry),
+            tokens,
+            force_refresh: false,
+            api_key: Some(config.api_key.trim().to_string()).filter(|key| !key.is_empty()),
+        }
+    }
+
+    pub fn access(&self) -> Access {
+        match (self.tokens.token(), &self.api_key) {
+            (Some(_), _) => Access::Account,
+            (None, Some(_)) => Access::ApiKey,
+            (None, None) => Access::SignedOut,
+        }
+    }
+
+    //what is left of today's quota, for the status bar
+    pub fn quota_remaining(&self) -> u32 {
+        self.quota.remaining(self.quota_limits)
+    }
+
+    //a copy of the client that revalidates every cached response instead of trusting it
+    pub fn force_refresh(&self) -> Self {
+        Self {
+            force_refresh: true,
+            ..self.clone()
+        }
+    }
+
+    //builds the url of an endpoint like "playlists" or "search"
+    fn endpoint(&self, path: &str) -> Result<Url> {
+        Url::parse(&format!("{}/{}", self.base_url, path))
+            .with_context(|| format!("Invalid API url for {}", path))
+    }
+
+    //sends an authorized GET request and returns the body of a successful response
+    //cost is the estimated quota cost of the call, it is checked against the budget before sending
+    //responses are cached per account, fresh ones are returned without a request
+    //and stale ones are revalidated with their ETag
+    async fn get(&self, url: Url, what: &str, cost: u32) -> Result<String> {
+        let account = self.tokens.account_key();
+        let cached = cache::load(&self.cache_dir, &account, url.as_str());
+        if let Some(entry) = &cached {
+            if !self.force_refresh && entry.is_fresh(self.cache_ttl) {
+                return Ok(entry.body.clone());
+            }
+        }
+
+        self.quota.check(cost, self.quota_limits)
+            .map_err(|remaining| ApiError::QuotaBudgetReached { remaining })?;
+        let request = Request {
+            method: Method::GET,
+            url: &url,
+            body: None,
+            etag: cached.as_ref().and_then(|entry| entry.etag.as_deref()),
+            what,
+            cost,
+            account_only: is_account_only(&url),
+        };
+        let fetched = self.send_with_retry(&request).await?;
+
+        let entry = match (fetched, cached) {
+            (Fetched::NotModified, Some(mut entry)) => {
+                entry.fetched_at = Utc::now();
+                entry
+            }
+            (Fetched::Body { text, etag }, _) => CacheEntry {
+                url: url.to_string(),
+                etag,
+                body: text,
+                fetched_at: Utc::now(),
+            },
+            //we only send If-None-Match when there is a cached entry
+            (Fetched::NotModified, None) => {
+                return Err(ApiError::Other { status: 304, message: "Not modified, but nothing is cached".to_string() }.into())
+            }
+        };
+        if let Err(e) = cache::store(&self.cache_dir, &account, &entry) {
+            eprintln!("Failed to write cache entry: {}", e);
+        }
+        Ok(entry.body)
+    }
+
+    //sends a request that changes something on youtube, like creating a playlist
+    //these are never cached, and the body of the answer is returned as it is
+    async fn write(&self, method: Method, url: Url, body: Option<&serde_json::Value>, what: &str, cost: u32) -> Result<String> {
+        self.quota.check(cost, self.quota_limits)
+            .map_err(|remaining| ApiError::QuotaBudgetReached { remaining })?;
+        let request = Request {
+            method,
+            url: &url,
+            body,
+            etag: None,
+            what,
+            cost,
+            //every change is made as the user
+            account_only: true,
+        };
+        match self.send_with_retry(&request).await? {
+            Fetched::Body { text, .. } => Ok(text),
+            Fetched::NotModified => Ok(String::new()),
+        }
+    }
+
+    //network errors, 5xx and 429 answers are retried with a growing, randomized wait
+    //while waiting the notifier gets a message, so the status bar can show what is going on
+    //POSTs are never retried, the first one may have gone through and we'd create things twice
+    async fn send_with_retry(&self, request: &Request<'_>) -> Result<Fetched> {
+        let mut attempt = 0;
+        loop {
+            let result = self.send_authorized(request).await;
+            let error = match result {
+                Ok(fetched) => {
+                    if attempt > 0 {
+                        (self.on_retry)(None);
+                    }
+                    return Ok(fetched);
+                }
+                Err(e) => e,
+            };
+
+            let api_error = error
+                .downcast_ref::<ApiError>()
+                .filter(|api_error| api_error.is_transient() && request.method != Method::POST);
+            attempt += 1;
+            let delay = api_error.and_then(|api_error| self.retry_policy.delay(attempt, api_error.retry_after()));
+            match delay {
+                Some(delay) => {
+                    (self.on_retry)(Some(format!(
+                        "{} request failed, retrying in {:.1}s (attempt {} of {})",
+                        request.what,
+                        delay.as_secs_f32(),
+                        attempt,
+                        self.retry_policy.max_retries
+                    )));
+                    tokio::time::sleep(delay).await;
+                }
+                None => {
+                    if attempt > 1 {
+                        (self.on_retry)(None);
+                    }
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    //sends the request with the current token, if it was rejected it is refreshed and the request is tried once more
+    //without a token public reads go out with the api key, if there is one
+    async fn send_authorized(&self, request: &Request<'_>) -> Result<Fetched> {
+        let token = match self.tokens.token() {
+            Some(token) => token,
+            None => {
+                return match (&self.api_key, request.account_only) {
+                    (Some(key), false) => self.send(request, Auth::Key(key)).await,
+                    _ => Err(ApiError::SignInRequired { what: request.what.to_string(), public: !request.account_only }.into()),
+                }
+            }
+        };
+        match self.send(request, Auth::Token(token.access_token().secret())).await {
+            Err(e) if matches!(e.downcast_ref::<ApiError>(), Some(ApiError::InvalidCredentials(_))) => {
+                let token = self.tokens.refresh().await?;
+                self.send(request, Auth::Token(token.access_token().secret())).await
+            }
+            result => result,
+        }
+    }
+
+    //a single request, failures come back as an ApiError so the caller can tell them apart
+    async fn send(&self, request: &Request<'_>, auth: Auth<'_>) -> Result<Fetched> {
+        let mut builder = match auth {
+            //oauth token with the bearer schema
+            Auth::Token(access_token) => self
+                .http
+                .request(request.method.clone(), request.url.clone())
+                .bearer_auth(access_token),
+            //the key goes into the url, but not into the cache, which keeps the url without it
+            Auth::Key(key) => {
+                let mut url = request.url.clone();
+                url.query_pairs_mut().append_pair("key", key);
+                self.http.request(request.method.clone(), url)
+            }
+        };
+        if let Some(etag) = request.etag {
+            builder = builder.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(body) = request.body {
+            builder = builder.json(body);
+        }
+        let response = builder
+            .send() //sending the request
+            .await //waiting for response
+            .map_err(|e| ApiError::Network(e.to_string()))
+            .with_context(|| format!("Failed to send {} request", request.what))?; //context if something goes wrong
+
+        //google charges for every request that reaches it, even the failed ones
+        self.quota.record(request.cost);
+
+        let status = response.status(); //status code for request reponse
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Fetched::NotModified);
+        }
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        //only the number of seconds form of Retry-After is used, google doesn't send dates
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ApiError::Network(e.to_string()))
+            .context("Failed to read response text")?;
+
+        if !status.is_success() {
+            return Err(ApiError::from_response(status.as_u16(), &text, retry_after).into());
+        }
+        Ok(Fetched::Body { text, etag })
+    }
+
+    //fetches all playlists of the user, following nextPageToken for at most max_pages pages
+    //on_page gets every page as it arrives together with the total number of playlists
+    pub async fn list_playlists<F>(&self, max_pages: usize, on_page: F) -> Result<Vec<Playlist>>
+    where
+        F: FnMut(&[Playlist], u32),
+    {
+        self.playlists_by(("mine", "true"), max_pages, on_page).await
+    }
+
+    //the public playlists of any channel, for the channel page
+    pub async fn channel_playlists(&self, channel_id: &str, max_pages: usize) -> Result<Vec<Playlist>> {
+        self.playlists_by(("channelId", channel_id), max_pages, |_, _| {}).await
+    }
+
+    //lists playlists, owner is the parameter that says whose, mine=true or channelId=...
+    async fn playlists_by<F>(
+        &self,
+        owner: (&str, &str),
+        max_pages: usize,
+        mut on_page: F,
+    ) -> Result<Vec<Playlist>>
+    where
+        F: FnMut(&[Playlist], u32),
+    {
+        let mut playlists = vec![];
+        let mut page_token: Option<String> = None;
+
+        for _ in 0..max_pages {
+            let mut url = self.endpoint("playlists")?;
+            url.query_pairs_mut()
+                .append_pair("part", "snippet,status")
+                .append_pair(owner.0, owner.1)
+                .append_pair("maxResults", "50");
+            if let Some(token) = &page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+
+            let text = self.get(url, "playlist", COST_READ).await?;
+            let playlists_response: PlaylistListResponse =
+                serde_json::from_str(&text).context("Failed to parse playlists JSON")?;
+
+            let page: Vec<Playlist> = playlists_response
+                .items
+                .into_iter()
+                .map(|item| Playlist {
+                    id: item.id,
+                    title: item.snippet.title,
+                    description: item.snippet.description,
+                    privacy: item.status.map(|status| status.privacy_status).unwrap_or_default(),
+                    system: false,
+                    local: false,
+                })
+                .collect();
+            let total = playlists_response
+                .page_info
+                .map(|info| info.total_results)
+                .unwrap_or(page.len() as u32);
+            on_page(&page, total);
+            playlists.extend(page);
+
+            page_token = playlists_response.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(playlists)
+    }
+
+    //creates a new playlist for the user and returns it
+    pub async fn create_playlist(&self, title: &str, description: &str, privacy: &str) -> Result<Playlist> {
+        let mut url = self.endpoint("playlists")?;
+        url.query_pairs_mut().append_pair("part", "snippet,status");
+        let body = serde_json::json!({
+            "snippet": { "title": title, "description": description },
+            "status": { "privacyStatus": privacy },
+        });
+
+        let text = self.write(Method::POST, url, Some(&body), "create playlist", COST_WRITE).await?;
+        let created: PlaylistItem =
+            serde_json::from_str(&text).context("Failed to parse created playlist")?;
+        Ok(Playlist {
+            id: created.id,
+            title: created.snippet.title,
+            description: created.snippet.description,
+            privacy: created.status.map(|status| status.privacy_status).unwrap_or_default(),
+            system: false,
+            local: false,
+        })
+    }
+
+    //saves the title, description and privacy of a playlist
+    //youtube replaces the whole snippet, so the description has to be sent even if it didn't change
+    pub async fn update_playlist(&self, playlist: &Playlist) -> Result<()> {
+        let mut url = self.endpoint("playlists")?;
+        url.query_pairs_mut().append_pair("part", "snippet,status");
+        let body = serde_json::json!({
+            "id": playlist.id,
+            "snippet": { "title": playlist.title, "description": playlist.description },
+            "status": { "privacyStatus": playlist.privacy },
+        });
+
+        self.write(Method::PUT, url, Some(&body), "update playlist", COST_WRITE).await?;
+        Ok(())
+    }
+
+    pub async fn delete_playlist(&self, playlist_id: &str) -> Result<()> {
+        let mut url = self.endpoint("playlists")?;
+        url.query_pairs_mut().append_pair("id", playlist_id);
+
+        self.write(Method::DELETE, url, None, "delete playlist", COST_WRITE).await?;
+        Ok(())
+    }
+
+    //adds a video to the end of a playlist
+    pub async fn add_to_playlist(&self, playlist_id: &str, video_id: &str) -> Result<()> {
+        let mut url = self.endpoint("playlistItems")?;
+        url.query_pairs_mut().append_pair("part", "snippet");
+        let body = serde_json::json!({
+            "snippet": {
+                "playlistId": playlist_id,
+                "resourceId": { "kind": "youtube#video", "videoId": video_id },
+            },
+        });
+
+        self.write(Method::POST, url, Some(&body), "add to playlist", COST_WRITE).await?;
+        Ok(())
+    }
+
+    pub async fn remove_from_playlist(&self, item_id: &str) -> Result<()> {
+        let mut url = self.endpoint("playlistItems")?;
+        url.query_pairs_mut().append_pair("id", item_id);
+
+        self.write(Method::DELETE, url, None, "remove from playlist", COST_WRITE).await?;
+        Ok(())
+    }
+
+    //moves an item to a new zero based position, the items in between shift by one
+    //youtube only allows this for playlists that are sorted manually
+    pub async fn move_playlist_item(&self, playlist_id: &str, entry: &PlaylistEntry, position: u32) -> Result<()> {
+        let mut url = self.endpoint("playlistItems")?;
+        url.query_pairs_mut().append_pair("part", "snippet");
+        let body = serde_json::json!({
+            "id": entry.item_id,
+            "snippet": {
+                "playlistId": playlist_id,
+                "resourceId": { "kind": "youtube#video", "videoId": entry.video_id },
+                "position": position,
+            },
+        });
+
+        self.write(Method::PUT, url, Some(&body), "move playlist item", COST_WRITE).await?;
+        Ok(())
+    }
+
+    //the playlists youtube keeps for the account itself, they are listed before the user's own
+    pub async fn system_playlists(&self) -> Result<Vec<Playlist>> {
+        let mut url = self.endpoint("channels")?;
+        url.query_pairs_mut()
+            .append_pair("part", "contentDetails")
+            .append_pair("mine", "true");
+
+        let body = self.get(url, "channel", COST_READ).await?;
+        let channels: ChannelListResponse =
+            serde_json::from_str(&body).context("Failed to parse channels response")?;
+        let related = match channels.items.into_iter().next() {
+            Some(channel) => channel.content_details.related_playlists,
+            None => return Ok(vec![]),
+        };
+
+        let playlists = vec![
+            (related.likes, "Liked videos", "private"),
+            (related.uploads, "Your uploads", "public"),
+            (related.watch_later, "Watch later", "private"),
+            (related.watch_history, "Watch history", "private"),
+        ];
+        Ok(playlists
+            .into_iter()
+            .filter(|(id, _, _)| !id.is_empty())
+            .map(|(id, title, privacy)| Playlist {
+                id,
+                title: title.to_string(),
+                description: String::new(),
+                privacy: privacy.to_string(),
+                system: true,
+                local: false,
+            })
+            .collect())
+    }
+
+    //fetches the videos of a playlist, paged the same way as list_playlists
+    pub async fn get_videos_from_playlist<F>(
+        &self,
+        playlist_id: &str,
+        max_pages: usize,
+        mut on_page: F,
+    ) -> Result<Vec<PlaylistEntry>>
+    where
+        F: FnMut(&[PlaylistEntry], u32),
+    {
+        let mut videos = vec![];
+        let mut page_token: Option<String> = None;
+
+        for _ in 0..max_pages {
+            let mut url = self.endpoint("playlistItems")?;
+            url.query_pairs_mut()
+                .append_pair("part", "snippet")
+                .append_pair("playlistId", playlist_id)
+                .append_pair("maxResults", "50");
+            if let Some(token) = &page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+
+            let text = self.get(url, "playlistItems", COST_READ).await?;
+            let playlist_items: PlaylistListResponse =
+                serde_json::from_str(&text).context("Failed to parse playlistItems JSON")?;
+
+            let total = playlist_items
+                .page_info
+                .as_ref()
+                .map(|info| info.total_results)
+                .unwrap_or(0);
+            //deleted and private videos have no resource id, those are skipped
+            let page: Vec<PlaylistEntry> = playlist_items
+                .items
+                .into_iter()
+                .filter_map(|item| {
+                    let resource = item.snippet.resource_id?;
+                    Some(PlaylistEntry {
+                        item_id: item.id,
+                        video_id: resource.video_id,
+                        title: item.snippet.title,
+                        uploader: item.snippet.video_owner_channel_title.unwrap_or_default(),
+                        channel_id: item.snippet.video_owner_channel_id.unwrap_or_default(),
+                        position: item.snippet.position,
+                    })
+                })
+                .collect();
+            on_page(&page, total);
+            videos.extend(page);
+
+            page_token = playlist_items.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(videos)
+    }
+
+    //searches for videos, playlists or channels, whichever the type filter asks for
+    //the filters the user set in the filter panel are added as extra search parameters
+    //page_token is the next_page_token of the previous page when loading more results
+    pub async fn search(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        page_token: Option<&str>,
+    ) -> Result<SearchPage> {
+        let mut url = self.endpoint("search")?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet")
+            .append_pair("q", query)
+            .append_pair("maxResults", &self.search_page_size.to_string());
+        for (name, value) in filters.query_pairs() {
+            url.query_pairs_mut().append_pair(name, &value);
+        }
+        if let Some(token) = page_token {
+            url.query_pairs_mut().append_pair("pageToken", token);
+        }
+
+        let body = self.get(url, "search", COST_SEARCH).await?;
+        let search_data: SearchListResponse =
+            serde_json::from_str(&body).context("Failed to parse search response")?;
+
+        //search doesn't return durations, those come from the video details
+        let video_ids: Vec<String> = search_data
+            .items
+            .iter()
+            .filter_map(|item| item.id.video_id.clone())
+            .collect();
+        let mut details: HashMap<String, Video> = self
+            .video_details(&video_ids)
+            .await?
+            .into_iter()
+            .map(|video| (video.id.clone(), video))
+            .collect();
+
+        //keeping the order youtube ranked the results in
+        let results = search_data
+            .items
+            .into_iter()
+            .filter_map(|item| match item.id.kind.as_str() {
+                //videos that have no details were removed or made private since
+                "youtube#video" => details.remove(&item.id.video_id?).map(SearchResult::Video),
+                "youtube#playlist" => Some(SearchResult::Playlist {
+                    id: item.id.playlist_id?,
+                    title: unescape_html(&item.snippet.title),
+                    uploader: unescape_html(&item.snippet.channel_title),
+                    channel_id: item.snippet.channel_id,
+                }),
+                "youtube#channel" => Some(SearchResult::Channel {
+                    id: item.id.channel_id?,
+                    title: unescape_html(&item.snippet.title),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Ok(SearchPage {
+            results,
+            next_page_token: search_data.next_page_token,
+        })
+    }
+
+    //fetches titles, uploaders and durations of videos, in the order the api returns them
+    //ids of removed or private videos are left out, the api takes at most 50 ids per request
+    pub async fn video_details(&self, video_ids: &[String]) -> Result<Vec<Video>> {
+        let mut videos = vec![];
+        for chunk in video_ids.chunks(50) {
+            let mut video_url = self.endpoint("videos")?;
+            video_url
+                .query_pairs_mut()
+                .append_pair("part", "snippet,contentDetails")
+                .append_pair("id", &chunk.join(","));
+
+            let body = self.get(video_url, "video details", COST_READ).await?;
+            let details: VideoListResponse =
+                serde_json::from_str(&body).context("Failed to parse video details response")?;
+
+            videos.extend(details.items.into_iter().map(|item| Video {
+                id: item.id,
+                title: item.snippet.title,
+                uploader: item.snippet.channel_title,
+                channel_id: item.snippet.channel_id,
+                duration: item.content_details.duration,
+            }));
+        }
+
+        Ok(videos)
+    }
+
+    //subscribes the user to a channel and returns the new subscription
+    pub async fn subscribe(&self, channel_id: &str) -> Result<Subscription> {
+        let mut url = self.endpoint("subscriptions")?;
+        url.query_pairs_mut().append_pair("part", "snippet");
+        let body = serde_json::json!({
+            "snippet": {
+                "resourceId": { "kind": "youtube#channel", "channelId": channel_id },
+            },
+        });
+
+        let text = self.write(Method::POST, url, Some(&body), "subscribe", COST_WRITE).await?;
+        let created: SubscriptionItem =
+            serde_json::from_str(&text).context("Failed to parse created subscription")?;
+        Ok(Subscription {
+            id: created.id,
+            channel_id: created.snippet.resource_id.channel_id,
+            title: created.snippet.title,
+        })
+    }
+
+    pub async fn unsubscribe(&self, subscription_id: &str) -> Result<()> {
+        let mut url = self.endpoint("subscriptions")?;
+        url.query_pairs_mut().append_pair("id", subscription_id);
+
+        self.write(Method::DELETE, url, None, "unsubscribe", COST_WRITE).await?;
+        Ok(())
+    }
+
+    //which of these channels the user is subscribed to, as channel id -> subscription id
+    //revalidated like the ratings, so a subscription made elsewhere a minute ago shows up
+    pub async fn subscription_ids(&self, channel_ids: &[String]) -> Result<HashMap<String, String>> {
+        let youtube = self.force_refresh();
+        let mut subscribed = HashMap::new();
+        for ids in channel_ids.chunks(50) {
+            let mut url = self.endpoint("subscriptions")?;
+            url.query_pairs_mut()
+                .append_pair("part", "snippet")
+                .append_pair("mine", "true")
+                .append_pair("forChannelId", &ids.join(","))
+                .append_pair("maxResults", "50");
+
+            let text = youtube.get(url, "subscriptions", COST_READ).await?;
+            let response: SubscriptionListResponse =
+                serde_json::from_str(&text).context("Failed to parse subscriptions JSON")?;
+            subscribed.extend(
+                response
+                    .items
+                    .into_iter()
+                    .map(|item| (item.snippet.resource_id.channel_id, item.id)),
+            );
+        }
+        Ok(subscribed)
+    }
+
+    //the description, statistics, tags and category of a single video
+    pub async fn video(&self, video_id: &str) -> Result<VideoDetails> {
+        let mut url = self.endpoint("videos")?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet,contentDetails,statistics")
+            .append_pair("id", video_id);
+
+        let body = self.get(url, "video details", COST_READ).await?;
+        let details: VideoListResponse =
+            serde_json::from_str(&body).context("Failed to parse video details response")?;
+        let item = match details.items.into_iter().next() {
+            Some(item) => item,
+            None => return Err(ApiError::NotFound(format!("video {}", video_id)).into()),
+        };
+        let count = |value: Option<String>| value.and_then(|value| value.parse().ok());
+        let statistics = item.statistics.unwrap_or_default();
+        //the details are still worth showing if the category name can't be looked up
+        let category = match self.video_category(&item.snippet.category_id).await {
+            Ok(Some(name)) => name,
+            _ => item.snippet.category_id.clone(),
+        };
+
+        Ok(VideoDetails {
+            id: item.id,
+            title: item.snippet.title,
+            uploader: item.snippet.channel_title,
+            description: item.snippet.description,
+            published_at: item.snippet.published_at,
+            duration: item.content_details.duration,
+            view_count: count(statistics.view_count),
+            like_count: count(statistics.like_count),
+            comment_count: count(statistics.comment_count),
+            tags: item.snippet.tags,
+            category,
+        })
+    }
+
+    //a page of the top level comments of a video, each with the first few of its replies
+    pub async fn comment_threads(&self, video_id: &str, order: CommentOrder, page_token: Option<&str>) -> Result<CommentPage> {
+        let mut url = self.endpoint("commentThreads")?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet,replies")
+            .append_pair("videoId", video_id)
+            .append_pair("order", order.api_value())
+            .append_pair("textFormat", "plainText")
+            .append_pair("maxResults", "20");
+        if let Some(token) = page_token {
+            url.query_pairs_mut().append_pair("pageToken", token);
+        }
+
+        let body = self.get(url, "comments", COST_READ).await?;
+        let response: CommentThreadListResponse =
+            serde_json::from_str(&body).context("Failed to parse comments response")?;
+        let threads = response
+            .items
+            .into_iter()
+            .map(|item| CommentThread {
+                id: item.snippet.top_level_comment.id.clone(),
+                comment: item.snippet.top_level_comment.into(),
+                reply_count: item.snippet.total_reply_count,
+                replies: item
+                    .replies
+                    .map(|replies| replies.comments.into_iter().map(Comment::from).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(CommentPage {
+            threads,
+            next_page_token: response.next_page_token,
+        })
+    }
+
+    //every reply to a comment, following nextPageToken for at most max_pages pages
+    pub async fn comment_replies(&self, comment_id: &str, max_pages: usize) -> Result<Vec<Comment>> {
+        let mut replies = vec![];
+        let mut page_token: Option<String> = None;
+
+        for _ in 0..max_pages {
+            let mut url = self.endpoint("comments")?;
+            url.query_pairs_mut()
+                .append_pair("part", "snippet")
+                .append_pair("parentId", comment_id)
+                .append_pair("textFormat", "plainText")
+                .append_pair("maxResults", "100");
+            if let Some(token) = &page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+
+            let body = self.get(url, "replies", COST_READ).await?;
+            let response: CommentListResponse =
+                serde_json::from_str(&body).context("Failed to parse replies response")?;
+            replies.extend(response.items.into_iter().map(Comment::from));
+
+            page_token = response.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(replies)
+    }
+
+    //the name of a video category, None if youtube doesn't know the id
+    async fn video_category(&self, category_id: &str) -> Result<Option<String>> {
+        if category_id.is_empty() {
+            return Ok(None);
+        }
+        let mut url = self.endpoint("videoCategories")?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet")
+            .append_pair("id", category_id);
+
+        let body = self.get(url, "video category", COST_READ).await?;
+        let categories: CategoryListResponse =
+            serde_json::from_str(&body).context("Failed to parse video categories response")?;
+        Ok(categories.items.into_iter().next().map(|item| item.snippet.title))
+    }
+
+    //every channel the user is subscribed to, following nextPageToken for at most max_pages pages
+    pub async fn list_subscriptions(&self, max_pages: usize) -> Result<Vec<Subscription>> {
+        let mut subscriptions = vec![];
+        let mut page_token: Option<String> = None;
+
+        for _ in 0..max_pages {
+            let mut url = self.endpoint("subscriptions")?;
+            url.query_pairs_mut()
+                .append_pair("part", "snippet")
+                .append_pair("mine", "true")
+                .append_pair("order", "alphabetical")
+                .append_pair("maxResults", "50");
+            if let Some(token) = &page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+
+            let text = self.get(url, "subscriptions", COST_READ).await?;
+            let response: SubscriptionListResponse =
+                serde_json::from_str(&text).context("Failed to parse subscriptions JSON")?;
+            subscriptions.extend(response.items.into_iter().map(|item| Subscription {
+                id: item.id,
+                channel_id: item.snippet.resource_id.channel_id,
+                title: item.snippet.title,
+            }));
+
+            page_token = response.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(subscriptions)
+    }
+
+    //the newest uploads of all these channels together, newest first
+    //the uploads playlists are looked up 50 channels at a time, then read a few at once
+    pub async fn latest_uploads(&self, channel_ids: &[String], per_channel: u32) -> Result<Vec<FeedVideo>> {
+        let mut uploads = vec![];
+        for ids in channel_ids.chunks(50) {
+            let mut url = self.endpoint("channels")?;
+            url.query_pairs_mut()
+                .append_pair("part", "contentDetails")
+                .append_pair("id", &ids.join(","))
+                .append_pair("maxResults", "50");
+
+            let body = self.get(url, "channels", COST_READ).await?;
+            let channels: ChannelListResponse =
+                serde_json::from_str(&body).context("Failed to parse channels response")?;
+            uploads.extend(
+                channels
+                    .items
+                    .into_iter()
+                    .map(|channel| channel.content_details.related_playlists.uploads),
+            );
+        }
+
+        let pages: Vec<Result<Vec<FeedVideo>>> = stream::iter(uploads)
+            .map(|playlist_id| async move { self.recent_uploads(&playlist_id, per_channel).await })
+            .buffer_unordered(8)
+            .collect()
+            .await;
+
+        let mut feed = vec![];
+        for page in pages {
+            match page {
+                Ok(videos) => feed.extend(videos),
+                //a channel without uploads has no uploads playlist to read
+                Err(e) if matches!(e.downcast_ref::<ApiError>(), Some(ApiError::NotFound(_))) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        feed.sort_by_key(|video| std::cmp::Reverse(video.published_at));
+        Ok(feed)
+    }
+
+    //the first page of an uploads playlist, with the publish date of every video
+    async fn recent_uploads(&self, playlist_id: &str, count: u32) -> Result<Vec<FeedVideo>> {
+        let mut url = self.endpoint("playlistItems")?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet,contentDetails")
+            .append_pair("playlistId", playlist_id)
+            .append_pair("maxResults", &count.clamp(1, 50).to_string());
+
+        let text = self.get(url, "uploads", COST_READ).await?;
+        let items: PlaylistListResponse =
+            serde_json::from_str(&text).context("Failed to parse playlistItems JSON")?;
+        let videos = items
+            .items
+            .into_iter()
+            .filter_map(|item| {
+                let published_at = item
+                    .content_details
+                    .and_then(|details| details.video_published_at)
+                    .or(item.snippet.published_at)?;
+                Some(FeedVideo {
+                    video_id: item.snippet.resource_id?.video_id,
+                    title: item.snippet.title,
+                    channel_title: item.snippet.video_owner_channel_title.unwrap_or_default(),
+                    published_at,
+                })
+            })
+            .collect();
+        Ok(videos)
+    }
+
+    //likes, dislikes or clears the rating of a video
+    pub async fn rate_video(&self, video_id: &str, rating: Rating) -> Result<()> {
+        let mut url = self.endpoint("videos/rate")?;
+        url.query_pairs_mut()
+            .append_pair("id", video_id)
+            .append_pair("rating", rating.api_value());
+
+        self.write(Method::POST, url, None, "rate video", COST_WRITE).await?;
+        Ok(())
+    }
+
+    //the ratings the user gave these videos, asked 50 at a time
+    //the cache is always revalidated, a rating changed a minute ago would show up wrong otherwise
+    pub async fn get_ratings(&self, video_ids: &[String]) -> Result<HashMap<String, Rating>> {
+        let youtube = self.force_refresh();
+        let mut ratings = HashMap::new();
+        for ids in video_ids.chunks(50) {
+            let mut url = self.endpoint("videos/getRating")?;
+            url.query_pairs_mut().append_pair("id", &ids.join(","));
+
+            let body = youtube.get(url, "rating", COST_READ).await?;
+            let response: RatingListResponse =
+                serde_json::from_str(&body).context("Failed to parse ratings response")?;
+            ratings.extend(
+                response
+                    .items
+                    .into_iter()
+                    .map(|item| (item.video_id, Rating::from_api(&item.rating))),
+            );
+        }
+        Ok(ratings)
+    }
+
+    //the title, description, subscriber count and uploads playlist of a channel
+    pub async fn channel(&self, channel_id: &str) -> Result<Channel> {
+        let mut url = self.endpoint("channels")?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet,statistics,contentDetails")
+            .append_pair("id", channel_id);
+
+        let body = self.get(url, "channel", COST_READ).await?;
+        let channels: ChannelListResponse =
+            serde_json::from_str(&body).context("Failed to parse channels response")?;
+        let item = match channels.items.into_iter().next() {
+            Some(item) => item,
+            None => return Err(ApiError::NotFound(format!("channel {}", channel_id)).into()),
+        };
+        let snippet = item.snippet.context("Channel response has no snippet")?;
+        let subscriber_count = item
+            .statistics
+            .filter(|statistics| !statistics.hidden_subscriber_count)
+            .and_then(|statistics| statistics.subscriber_count)
+            .and_then(|count| count.parse().ok());
+
+        Ok(Channel {
+            id: channel_id.to_string(),
+            title: snippet.title,
+            description: snippet.description,
+            subscriber_count,
+            uploads: item.content_details.related_playlists.uploads,
+        })
+    }
+}
+
+//reads about the user's own account, like their playlists or ratings, mine=true is how the api asks for those
+fn is_account_only(url: &Url) -> bool {
+    url.path().ends_with("/getRating") || url.query_pairs().any(|(key, value)| key == "mine" && value == "true")
+}
+
+//search snippets come html escaped, unlike the video details
+fn unescape_html(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+
+    //every client gets a directory of its own for its cache and quota, so tests running at the same time don't share them
+    fn temp_dir() -> PathBuf {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "yt_tui_youtube_{}_{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn token(access_token: &str) -> OAuthToken {
+        serde_json::from_value(serde_json::json!({
+            "access_token": access_token,
+            "token_type": "bearer",
+            "expires_in": 3600,
+        }))
+        .unwrap()
+    }
+
+    //hands out "old" until it is refreshed, then "new"
+    struct TestTokens {
+        current: Mutex<OAuthToken>,
+        refreshes: AtomicUsize,
+        account: String,
+    }
+
+    impl TestTokens {
+        fn new(account: &str) -> Arc<Self> {
+            Arc::new(Self { current: Mutex::new(token("old")), refreshes: AtomicUsize::new(0), account: account.to_string() })
+        }
+    }
+
+    impl TokenSource for TestTokens {
+        fn token(&self) -> Option<OAuthToken> {
+            Some(self.current.lock().unwrap().clone())
+        }
+
+        fn refresh(&self) -> BoxFuture<'_, Result<OAuthToken>> {
+            Box::pin(async move {
+                self.refreshes.fetch_add(1, Ordering::SeqCst);
+                let new = token("new");
+                *self.current.lock().unwrap() = new.clone();
+                Ok(new)
+            })
+        }
+
+        fn account_key(&self) -> String {
+            self.account.clone()
+        }
+    }
+
+    //what the mock server answers: status, body and an optional ETag
+    type Reply = (u16, &'static str, Option<&'static str>);
+
+    //what the mock server was sent: the Authorization and If-None-Match headers
+    #[derive(Debug, Clone, PartialEq)]
+    struct Seen {
+        authorization: Option<String>,
+        if_none_match: Option<String>,
+    }
+
+    //answers one request with each reply in order, then stops
+    fn serve(replies: Vec<Reply>) -> (String, thread::JoinHandle<Vec<Seen>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let handle = thread::spawn(move || {
+            let mut seen = vec![];
+            for (status, body, etag) in replies {
+                let request = server.recv().unwrap();
+                let header = |name: &'static str| {
+                    request
+                        .headers()
+                        .iter()
+                        .find(|header| header.field.equiv(name))
+                        .map(|header| header.value.as_str().to_string())
+                };
+                seen.push(Seen { authorization: header("Authorization"), if_none_match: header("If-None-Match") });
+                let mut response = tiny_http::Response::from_string(body).with_status_code(status);
+                if let Some(etag) = etag {
+                    response = response.with_header(tiny_http::Header::from_bytes(&b"ETag"[..], etag.as_bytes()).unwrap());
+                }
+                request.respond(response).unwrap();
+            }
+            seen
+        });
+        (base_url, handle)
+    }
+
+    fn client(base_url: &str, tokens: Arc<TestTokens>, retries: Arc<Mutex<Vec<Option<String>>>>) -> YouTubeClient {
+        let dir = temp_dir();
+        let config = Config {
+            api_base_url: base_url.to_string(),
+            cache_dir: dir.join("cache").to_string_lossy().into_owned(),
+            quota_file: dir.join("quota.json").to_string_lossy().into_owned(),
+            //every get goes to the server, stale entries are revalidated
+            cache_ttl_secs: 0,
+            retry_base_delay_ms: 1,
+            retry_max_delay_ms: 10,
+            ..Config::default()
+        };
+        YouTubeClient::new(&config, tokens, move |status| retries.lock().unwrap().push(status))
+    }
+
+    fn bearer(access_token: &str) -> Option<String> {
+        Some(format!("Bearer {}", access_token))
+    }
+
+    #[tokio::test]
+    async fn get_revalidates_with_etag() {
+        let (base_url, server) = serve(vec![(200, r#"{"items":[]}"#, Some("\"v1\"")), (304, "", None)]);
+        let youtube = client(&base_url, TestTokens::new("etag"), Arc::default());
+        let url = youtube.endpoint("videos?id=etag").unwrap();
+
+        assert_eq!(youtube.get(url.clone(), "test", COST_READ).await.unwrap(), r#"{"items":[]}"#);
+        //the 304 has no body, the cached one is returned
+        assert_eq!(youtube.get(url, "test", COST_READ).await.unwrap(), r#"{"items":[]}"#);
+
+        let seen = server.join().unwrap();
+        assert_eq!(seen[0], Seen { authorization: bearer("old"), if_none_match: None });
+        assert_eq!(seen[1], Seen { authorization: bearer("old"), if_none_match: Some("\"v1\"".to_string()) });
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried() {
+        let (base_url, server) = serve(vec![(503, "", None), (200, "ok", None)]);
+        let retries = Arc::default();
+        let youtube = client(&base_url, TestTokens::new("retry"), Arc::clone(&retries));
+        let url = youtube.endpoint("videos?id=retry").unwrap();
+
+        assert_eq!(youtube.get(url, "test", COST_READ).await.unwrap(), "ok");
+        assert_eq!(server.join().unwrap().len(), 2);
+        //the status bar is told about the wait and cleared once the retry worked
+        let retries = retries.lock().unwrap();
+        assert_eq!(retries.len(), 2);
+        assert!(retries[0].as_deref().unwrap().starts_with("test request failed, retrying in"));
+        assert_eq!(retries[1], None);
+    }
+
+    #[tokio::test]
+    async fn rejected_token_is_refreshed_once() {
+        let unauthorized = r#"{"error":{"code":401,"message":"Invalid Credentials","errors":[{"reason":"authError"}]}}"#;
+        let (base_url, server) = serve(vec![(401, unauthorized, None), (200, "ok", None)]);
+        let tokens = TestTokens::new("refresh");
+        let youtube = client(&base_url, Arc::clone(&tokens), Arc::default());
+        let url = youtube.endpoint("videos?id=refresh").unwrap();
+
+        assert_eq!(youtube.get(url, "test", COST_READ).await.unwrap(), "ok");
+        assert_eq!(tokens.refreshes.load(Ordering::SeqCst), 1);
+        let seen = server.join().unwrap();
+        assert_eq!(seen[0].authorization, bearer("old"));
+        assert_eq!(seen[1].authorization, bearer("new"));
+    }
+}